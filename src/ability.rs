@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+use crate::status::Inflict;
+
+// デフォルトの威力（通常攻撃と同じ）
+fn default_power() -> u32 {
+    100
+}
+
+// デフォルトの使用確率（条件を満たせば必ず使う）
+fn default_chance() -> f64 {
+    1.0
+}
+
+// 特技の効果
+// YAML では type フィールドで種類を指定する
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AbilityEffect {
+    // 通常攻撃の power% のダメージ。charge_turns があればそのターン数だけ力をためてから放つ
+    Strike {
+        #[serde(default = "default_power")]
+        power: u32,
+        #[serde(default)]
+        charge_turns: u32,
+        #[serde(default)]
+        inflict: Option<Inflict>,
+    },
+    // 最大HPの percent% を回復する
+    Heal {
+        percent: u32,
+    },
+    // 手下を呼ぶ（monster はモンスター名）
+    Summon {
+        monster: String,
+    },
+    // 怒ってバトルの間ずっと攻撃力を上げる
+    Enrage {
+        attack: u32,
+    },
+}
+
+// モンスターの特技
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Ability {
+    pub id: String, // behavior から参照するID
+    pub name: String,
+    pub effect: AbilityEffect,
+}
+
+// 行動パターンの1項目
+// 上から順に条件を確認し、最初に当たった特技を使う（どれにも当たらなければ通常攻撃）
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BehaviorRule {
+    pub ability: String, // 使う特技のID
+    // HPがこの割合(%)を下回っているときだけ使う
+    #[serde(default)]
+    pub hp_below: Option<u32>,
+    // このターン数ごとに使う（3なら3, 6, 9ターン目）
+    #[serde(default)]
+    pub every: Option<u32>,
+    // バトル中に1回だけ使う
+    #[serde(default)]
+    pub once: bool,
+    // 条件を満たしたときに使う確率 (0.0-1.0)
+    #[serde(default = "default_chance")]
+    pub chance: f64,
+}
+
+impl BehaviorRule {
+    // 残りHPと行動回数から、このルールの条件を満たしているか判定する（確率は含まない）
+    pub fn matches(&self, hp: u32, max_hp: u32, turn: u32) -> bool {
+        if let Some(percent) = self.hp_below {
+            if hp * 100 >= max_hp * percent {
+                return false;
+            }
+        }
+        if let Some(every) = self.every {
+            if !turn.is_multiple_of(every) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+// 特技と行動パターンの整合性を確認する
+pub(crate) fn validate_abilities(
+    abilities: &[Ability],
+    behavior: &[BehaviorRule],
+    monster_names: &[&str],
+) -> std::result::Result<(), String> {
+    for (i, ability) in abilities.iter().enumerate() {
+        if abilities[..i].iter().any(|other| other.id == ability.id) {
+            return Err(format!("特技ID {} が重複しています", ability.id));
+        }
+        match &ability.effect {
+            AbilityEffect::Strike {
+                inflict: Some(inflict),
+                ..
+            } => inflict.validate()?,
+            AbilityEffect::Summon { monster } if !monster_names.contains(&monster.as_str()) => {
+                return Err(format!(
+                    "特技 {} で呼ぶモンスター {} が見つかりません",
+                    ability.id, monster
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    for rule in behavior {
+        if !abilities.iter().any(|ability| ability.id == rule.ability) {
+            return Err(format!(
+                "behavior の特技 {} が abilities にありません",
+                rule.ability
+            ));
+        }
+        if rule.every == Some(0) {
+            return Err("every は1以上である必要があります".to_string());
+        }
+        if !(0.0..=1.0).contains(&rule.chance) {
+            return Err(format!(
+                "behavior の chance ({}) は 0.0 から 1.0 の範囲である必要があります",
+                rule.chance
+            ));
+        }
+    }
+
+    Ok(())
+}
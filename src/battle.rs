@@ -0,0 +1,1321 @@
+use std::cmp::Reverse;
+
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ability::{Ability, AbilityEffect},
+    character::{check_level_up, Character, LevelUp, PartyMember},
+    content::Content,
+    defeat::{Hardcore, Respawn},
+    element::{Affinity, Element},
+    equipment::{equipment_bonus, StatBonus},
+    item::{heal, restore_mp, Item, ItemEffect},
+    monster::{roll_loot, Monster},
+    skill::{Skill, SkillEffect},
+    status::{self, StatusEffect, StatusKind},
+    GameRng,
+};
+
+// バトル中にプレイヤーが選べる行動
+// usize は狙うモンスターの番号（Battle::enemies の添字）
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Attack(usize),       // 攻撃
+    Recover,             // 回復
+    Defend,              // 防御（このターンの被ダメージを半減）
+    Flee,                // 逃げる
+    UseItem(Item),       // アイテムを使う
+    Skill(Skill, usize), // スキルを使う（MPを消費する）
+    Auto,                // オート（従来のランダム行動）
+}
+
+// バトル中の一時的な効果の種類
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BuffKind {
+    Attack,  // 攻撃力アップ
+    Defense, // 防御力アップ
+    Regen,   // 毎ターンHP回復
+}
+
+impl BuffKind {
+    // 効果の日本語名を返す
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuffKind::Attack => "攻撃力アップ",
+            BuffKind::Defense => "防御力アップ",
+            BuffKind::Regen => "リジェネ",
+        }
+    }
+}
+
+// 一時的な効果（効果量, 残りターン数）
+#[derive(Debug, Clone, Copy)]
+struct Buff {
+    member: usize, // 効果を受けているパーティメンバーの番号
+    kind: BuffKind,
+    amount: u32,
+    turns: u32,
+}
+
+// バトルの結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BattleResult {
+    Win,     // 勝利
+    Lose,    // 敗北
+    Escaped, // 逃走成功
+}
+
+// バトル中に起きた出来事
+// エンジンは表示を行わず、フロントエンドがこれを受け取って描画する
+// JSON では "type" フィールドにイベント名（snake_case）が入る
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BattleEvent {
+    // ステージ挑戦開始
+    StageStarted {
+        stage: u32,
+        name: String,
+        battles: usize,
+    },
+    // ステージクリア
+    StageCleared {
+        stage: u32,
+        name: String,
+    },
+    // ステージ失敗（ボス戦で負けた場合は boss にボス名が入る）
+    StageFailed {
+        stage: u32,
+        name: String,
+        boss: Option<String>,
+    },
+    // モンスターが現れた
+    Encounter {
+        name: String,
+        hp: u32,
+    },
+    // パーティメンバーの攻撃（actor は行動したメンバー、target は狙ったモンスターの名前）
+    Attack {
+        actor: String,
+        target: String,
+        damage: u32,
+    },
+    // パーティメンバーの回復
+    Heal {
+        actor: String,
+        amount: u32,
+    },
+    // パーティメンバーの防御
+    Defend {
+        actor: String,
+    },
+    // 逃走成功
+    FleeSucceeded,
+    // 逃走失敗
+    FleeFailed,
+    // ボス戦なので逃げられない（ターンは消費しない）
+    CannotFlee,
+    // 使えるアイテムが無い（ターンは消費しない）
+    NoItem,
+    // アイテムを使った
+    ItemUsed {
+        actor: String,
+        name: String,
+    },
+    // ここでは使えないアイテム（ターンは消費しない）
+    ItemNotUsable {
+        name: String,
+    },
+    // MPを回復した
+    MpRestored {
+        actor: String,
+        amount: u32,
+    },
+    // 攻撃力が上がった（target は効果を受けたメンバーの名前）
+    AttackBuffed {
+        target: String,
+        amount: u32,
+        turns: u32,
+    },
+    // 防御力が上がった
+    DefenseBuffed {
+        target: String,
+        amount: u32,
+        turns: u32,
+    },
+    // 毎ターンHPが回復するようになった
+    RegenStarted {
+        target: String,
+        amount: u32,
+        turns: u32,
+    },
+    // 継続回復でHPが回復した
+    Regenerated {
+        target: String,
+        amount: u32,
+    },
+    // 一時的な効果が切れた
+    BuffExpired {
+        target: String,
+        buff: BuffKind,
+    },
+    // スキルを使った（MPを消費済み）
+    SkillUsed {
+        actor: String,
+        name: String,
+        mp_cost: u32,
+    },
+    // MPが足りない（ターンは消費しない）
+    NotEnoughMp {
+        name: String,
+    },
+    // 属性魔法が当たった
+    SpellHit {
+        target: String,
+        element: Element,
+        damage: u32,
+    },
+    // 状態異常にかかった（target はかかった側の名前）
+    StatusInflicted {
+        target: String,
+        status: StatusKind,
+        turns: u32,
+    },
+    // 状態異常にならなかった
+    StatusMissed {
+        target: String,
+        status: StatusKind,
+    },
+    // 毒・やけどのダメージ
+    StatusDamage {
+        target: String,
+        status: StatusKind,
+        damage: u32,
+    },
+    // 状態異常で行動できなかった
+    StatusBlocked {
+        target: String,
+        status: StatusKind,
+    },
+    // 状態異常が治った
+    StatusRecovered {
+        target: String,
+        status: StatusKind,
+    },
+    // モンスターが特技を使った
+    MonsterAbility {
+        monster: String,
+        ability: String,
+    },
+    // モンスターが力をためている
+    Charging {
+        monster: String,
+        ability: String,
+    },
+    // モンスターがHPを回復した
+    MonsterHealed {
+        monster: String,
+        amount: u32,
+    },
+    // モンスターが怒って攻撃力が上がった
+    Enraged {
+        monster: String,
+        attack: u32,
+    },
+    // ボスが次の形態に変わった（name は新しい形態の名前）
+    PhaseChanged {
+        monster: String,
+        name: String,
+        hp: u32,
+        dialogue: Vec<String>,
+    },
+    // モンスターが手下を呼んだ（続けて手下の Encounter が来る）
+    Summoned {
+        monster: String,
+        minion: String,
+    },
+    // 弱点を突いた
+    Effective {
+        element: Element,
+    },
+    // 耐性があり、ダメージが半減された
+    Resisted {
+        element: Element,
+    },
+    // モンスターからの被ダメージ（target は攻撃を受けたメンバーの名前）
+    DamageTaken {
+        monster: String,
+        target: String,
+        damage: u32,
+    },
+    // パーティメンバーが倒れた（全員倒れると Defeat が続く）
+    MemberDown {
+        name: String,
+    },
+    // 仲間が加わった
+    MemberJoined {
+        name: String,
+    },
+    // パーティが満員で仲間にできなかった
+    PartyFull {
+        name: String,
+    },
+    // ステージをクリアして、ショップの在庫が補充された
+    ShopRestocked,
+    // モンスターを倒した
+    MonsterDefeated {
+        name: String,
+    },
+    // 経験値を獲得した
+    ExpGained {
+        exp: u32,
+    },
+    // ゴールドを獲得した
+    GoldGained {
+        gold: u32,
+    },
+    // アイテムを手に入れた（item はアイテムID）
+    ItemDropped {
+        item: String,
+        count: u32,
+    },
+    // レベルアップした
+    LevelUp(LevelUp),
+    // パーティが全滅した
+    Defeat,
+    // 全滅のペナルティでゴールドを失った
+    GoldLost {
+        gold: u32,
+    },
+    // 全滅のペナルティで経験値を失った
+    ExpLost {
+        name: String,
+        exp: u32,
+    },
+    // 全滅から復活した
+    Revived {
+        respawn: Respawn,
+    },
+    // ハードコアで全滅し、ゲームオーバーになった
+    GameOver {
+        hardcore: Hardcore,
+    },
+}
+
+impl BattleEvent {
+    // イベント名を返す（JSON の "type" と同じ）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BattleEvent::StageStarted { .. } => "stage_started",
+            BattleEvent::StageCleared { .. } => "stage_cleared",
+            BattleEvent::StageFailed { .. } => "stage_failed",
+            BattleEvent::Encounter { .. } => "encounter",
+            BattleEvent::Attack { .. } => "attack",
+            BattleEvent::Heal { .. } => "heal",
+            BattleEvent::Defend { .. } => "defend",
+            BattleEvent::FleeSucceeded => "flee_succeeded",
+            BattleEvent::FleeFailed => "flee_failed",
+            BattleEvent::CannotFlee => "cannot_flee",
+            BattleEvent::NoItem => "no_item",
+            BattleEvent::ItemUsed { .. } => "item_used",
+            BattleEvent::ItemNotUsable { .. } => "item_not_usable",
+            BattleEvent::MpRestored { .. } => "mp_restored",
+            BattleEvent::AttackBuffed { .. } => "attack_buffed",
+            BattleEvent::DefenseBuffed { .. } => "defense_buffed",
+            BattleEvent::RegenStarted { .. } => "regen_started",
+            BattleEvent::Regenerated { .. } => "regenerated",
+            BattleEvent::BuffExpired { .. } => "buff_expired",
+            BattleEvent::SkillUsed { .. } => "skill_used",
+            BattleEvent::NotEnoughMp { .. } => "not_enough_mp",
+            BattleEvent::SpellHit { .. } => "spell_hit",
+            BattleEvent::StatusInflicted { .. } => "status_inflicted",
+            BattleEvent::StatusMissed { .. } => "status_missed",
+            BattleEvent::StatusDamage { .. } => "status_damage",
+            BattleEvent::StatusBlocked { .. } => "status_blocked",
+            BattleEvent::StatusRecovered { .. } => "status_recovered",
+            BattleEvent::MonsterAbility { .. } => "monster_ability",
+            BattleEvent::Charging { .. } => "charging",
+            BattleEvent::MonsterHealed { .. } => "monster_healed",
+            BattleEvent::Enraged { .. } => "enraged",
+            BattleEvent::Summoned { .. } => "summoned",
+            BattleEvent::PhaseChanged { .. } => "phase_changed",
+            BattleEvent::Effective { .. } => "effective",
+            BattleEvent::Resisted { .. } => "resisted",
+            BattleEvent::DamageTaken { .. } => "damage_taken",
+            BattleEvent::MemberDown { .. } => "member_down",
+            BattleEvent::MemberJoined { .. } => "member_joined",
+            BattleEvent::PartyFull { .. } => "party_full",
+            BattleEvent::ShopRestocked => "shop_restocked",
+            BattleEvent::MonsterDefeated { .. } => "monster_defeated",
+            BattleEvent::ExpGained { .. } => "exp_gained",
+            BattleEvent::GoldGained { .. } => "gold_gained",
+            BattleEvent::ItemDropped { .. } => "item_dropped",
+            BattleEvent::LevelUp(_) => "level_up",
+            BattleEvent::Defeat => "defeat",
+            BattleEvent::GoldLost { .. } => "gold_lost",
+            BattleEvent::ExpLost { .. } => "exp_lost",
+            BattleEvent::Revived { .. } => "revived",
+            BattleEvent::GameOver { .. } => "game_over",
+        }
+    }
+}
+
+// 同時に場に出られるモンスターの最大数（群れの大きさと手下を呼べる上限）
+pub const MAX_ENEMIES: usize = 4;
+
+// バトル中のモンスター1体の状態
+pub struct Enemy {
+    monster: Monster,
+    // 同じ種類が複数いるときに名前の後ろにつける記号（A, B, ...）
+    suffix: String,
+    // 出現したときのモンスター名（記号を数えるのに使う）
+    species: String,
+    hp: u32,
+    status: Vec<StatusEffect>,
+    // 行動したターン数（行動パターンの every 判定に使う）
+    turns: u32,
+    // 怒りによる攻撃力の上昇分
+    rage: u32,
+    // 力をためている特技（abilities の添字, 放つまでの残りターン数）
+    charging: Option<(usize, u32)>,
+    // 使用済みの once ルール（behavior の添字）
+    used: Vec<usize>,
+    // 次に変わる形態（monster.phases の添字）
+    next_phase: usize,
+}
+
+impl Enemy {
+    fn new(mut monster: Monster, suffix: String) -> Self {
+        let species = monster.name.clone();
+        monster.name.push_str(&suffix);
+        Enemy {
+            hp: monster.hp,
+            monster,
+            suffix,
+            species,
+            status: Vec::new(),
+            turns: 0,
+            rage: 0,
+            charging: None,
+            used: Vec::new(),
+            next_phase: 0,
+        }
+    }
+
+    // 表示用のモンスター（name には A, B などの記号がついている）
+    pub fn monster(&self) -> &Monster {
+        &self.monster
+    }
+
+    pub fn hp(&self) -> u32 {
+        self.hp
+    }
+
+    pub fn status(&self) -> &[StatusEffect] {
+        &self.status
+    }
+
+    // まだ倒されていないか
+    pub fn is_alive(&self) -> bool {
+        self.hp > 0
+    }
+
+    // 次の形態があれば変身してイベントを返す
+    // 状態異常や溜め、行動パターンの記録はリセットされる
+    fn transform(&mut self) -> Option<BattleEvent> {
+        let phase = self.monster.phases.get(self.next_phase)?.clone();
+        let before = self.monster.name.clone();
+
+        self.monster.transform(&phase);
+        if phase.name.is_some() {
+            self.monster.name.push_str(&self.suffix);
+        }
+        self.next_phase += 1;
+        self.hp = self.monster.hp;
+        self.status.clear();
+        self.turns = 0;
+        self.rage = 0;
+        self.charging = None;
+        self.used.clear();
+
+        Some(BattleEvent::PhaseChanged {
+            monster: before,
+            name: self.monster.name.clone(),
+            hp: self.hp,
+            dialogue: phase.dialogue,
+        })
+    }
+}
+
+// 1ターンの中で行動する者
+#[derive(Debug, Clone, Copy)]
+enum Actor {
+    Member(usize), // パーティメンバー（0 は主人公）
+    Enemy(usize),  // モンスター（enemies の添字）
+}
+
+// モンスターの群れとのバトルの状態
+pub struct Battle {
+    // 場に出たモンスター（倒したものも添字がずれないよう残しておく）
+    enemies: Vec<Enemy>,
+    // ボス戦（逃げられない）
+    boss: bool,
+    result: Option<BattleResult>,
+    // 攻撃力アップなどの一時的な効果
+    buffs: Vec<Buff>,
+}
+
+impl Battle {
+    // 同じ種類のモンスターが複数いれば、名前に A, B, ... をつけて見分けられるようにする
+    pub fn new(monsters: Vec<Monster>) -> Self {
+        let mut enemies: Vec<Enemy> = Vec::with_capacity(monsters.len());
+        for monster in &monsters {
+            let same = monsters.iter().filter(|m| m.name == monster.name).count();
+            let suffix = if same > 1 {
+                let index = enemies.iter().filter(|e| e.species == monster.name).count();
+                enemy_suffix(index)
+            } else {
+                String::new()
+            };
+            enemies.push(Enemy::new(monster.clone(), suffix));
+        }
+
+        Battle {
+            boss: monsters.iter().any(|m| m.is_boss),
+            enemies,
+            result: None,
+            buffs: Vec::new(),
+        }
+    }
+
+    // バトル開始時のイベント（モンスターの出現）を返す
+    pub fn encounter(&self) -> Vec<BattleEvent> {
+        self.enemies
+            .iter()
+            .map(|enemy| BattleEvent::Encounter {
+                name: enemy.monster.name.clone(),
+                hp: enemy.hp,
+            })
+            .collect()
+    }
+
+    // 場に出たモンスター（倒したものも含む。Action の対象はこの添字で指定する）
+    pub fn enemies(&self) -> &[Enemy] {
+        &self.enemies
+    }
+
+    // まだ倒されていないモンスターの番号
+    pub fn living_enemies(&self) -> Vec<usize> {
+        (0..self.enemies.len())
+            .filter(|&j| self.enemies[j].is_alive())
+            .collect()
+    }
+
+    // 決着がついていれば結果を返す
+    pub fn result(&self) -> Option<BattleResult> {
+        self.result
+    }
+
+    // 逃げられるバトルか（ボス戦では逃げられない）
+    pub fn can_flee(&self) -> bool {
+        !self.boss
+    }
+
+    // 逃げられる確率（0.05～0.95）
+    // 基本は50%で、主人公と一番レベルの高いモンスターのレベル差1につき5%、
+    // 一番素早いメンバーと一番素早いモンスターの素早さの差1につき2%増減する
+    pub fn flee_chance(&self, character: &Character) -> f64 {
+        if !self.can_flee() {
+            return 0.0;
+        }
+
+        let living = self.living_enemies();
+        let enemy_lv = living
+            .iter()
+            .map(|&j| self.enemies[j].monster.lv)
+            .max()
+            .unwrap_or(0);
+        let enemy_speed = living
+            .iter()
+            .map(|&j| self.enemies[j].monster.speed)
+            .max()
+            .unwrap_or(0);
+        let party_speed = character
+            .living_members()
+            .into_iter()
+            .map(|i| character.member(i).stats.speed)
+            .max()
+            .unwrap_or(0);
+
+        let lv_diff = character.hero.lv as f64 - enemy_lv as f64;
+        let speed_diff = party_speed as f64 - enemy_speed as f64;
+        (0.5 + lv_diff * 0.05 + speed_diff * 0.02).clamp(0.05, 0.95)
+    }
+
+    // 狙ったモンスターが既に倒れていれば、残っている先頭のモンスターに狙いを変える
+    fn retarget(&self, target: usize) -> usize {
+        match self.enemies.get(target) {
+            Some(enemy) if enemy.is_alive() => target,
+            _ => self.living_enemies().first().copied().unwrap_or(0),
+        }
+    }
+
+    // メンバーにかかっている、指定した種類の一時的な効果の合計量
+    fn buff_amount(&self, member: usize, kind: BuffKind) -> u32 {
+        self.buffs
+            .iter()
+            .filter(|buff| buff.member == member && buff.kind == kind)
+            .map(|buff| buff.amount)
+            .sum()
+    }
+
+    // 一時的な効果をかける（同じ種類の効果は上書きする）
+    fn add_buff(&mut self, member: usize, kind: BuffKind, amount: u32, turns: u32) {
+        self.buffs
+            .retain(|buff| buff.member != member || buff.kind != kind);
+        self.buffs.push(Buff {
+            member,
+            kind,
+            amount,
+            turns,
+        });
+    }
+
+    // 通常攻撃1回分のダメージ
+    fn roll_attack(
+        &self,
+        member: usize,
+        attacker: &PartyMember,
+        bonus: &StatBonus,
+        rng: &mut GameRng,
+    ) -> u32 {
+        rng.gen_range(attacker.stats.min_attack..=attacker.stats.max_attack)
+            + bonus.attack
+            + self.buff_amount(member, BuffKind::Attack)
+    }
+
+    // 属性の相性を知らせるイベント（普通なら無し）
+    fn affinity_event(element: Element, affinity: Affinity) -> Option<BattleEvent> {
+        match affinity {
+            Affinity::Normal => None,
+            Affinity::Effective => Some(BattleEvent::Effective { element }),
+            Affinity::Resisted => Some(BattleEvent::Resisted { element }),
+        }
+    }
+
+    // 武器による物理攻撃を当てる（武器に属性があれば相性で補正する）
+    fn physical_hit(
+        &mut self,
+        actor: &str,
+        target: usize,
+        element: Option<Element>,
+        damage: u32,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        let enemy = &mut self.enemies[target];
+        let affinity = element.map_or(Affinity::Normal, |e| enemy.monster.affinity(e));
+        let damage = affinity.apply(damage);
+
+        enemy.hp = enemy.hp.saturating_sub(damage);
+        events.push(BattleEvent::Attack {
+            actor: actor.to_string(),
+            target: enemy.monster.name.clone(),
+            damage,
+        });
+        events.extend(element.and_then(|e| Self::affinity_event(e, affinity)));
+    }
+
+    // 属性魔法を当てる
+    fn spell_hit(
+        &mut self,
+        target: usize,
+        element: Element,
+        damage: u32,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        let enemy = &mut self.enemies[target];
+        let affinity = enemy.monster.affinity(element);
+        let damage = affinity.apply(damage);
+
+        enemy.hp = enemy.hp.saturating_sub(damage);
+        events.push(BattleEvent::SpellHit {
+            target: enemy.monster.name.clone(),
+            element,
+            damage,
+        });
+        events.extend(Self::affinity_event(element, affinity));
+    }
+
+    // このターンの行動順（素早さの高い順、同じならパーティが先）
+    fn turn_order(&self, character: &Character) -> Vec<Actor> {
+        let mut order: Vec<(u32, Actor)> = character
+            .living_members()
+            .into_iter()
+            .map(|i| (character.member(i).stats.speed, Actor::Member(i)))
+            .collect();
+        for j in self.living_enemies() {
+            order.push((self.enemies[j].monster.speed, Actor::Enemy(j)));
+        }
+        order.sort_by_key(|(speed, _)| Reverse(*speed));
+        order.into_iter().map(|(_, actor)| actor).collect()
+    }
+
+    // 1ターン分を処理し、起きた出来事を順に返す
+    // actions はパーティメンバーの順（0 が主人公）で、足りない分は Action::Auto になる
+    // Action::Auto は従来どおりランダムに攻撃か回復を選ぶ（攻撃の対象もランダム）
+    // content は装備の効果、手下の呼び出し、レベルアップ時に覚えるスキルの判定に使う
+    pub fn turn(
+        &mut self,
+        character: &mut Character,
+        actions: Vec<Action>,
+        content: &Content,
+        rng: &mut GameRng,
+    ) -> Vec<BattleEvent> {
+        let mut events = Vec::new();
+
+        if self.result.is_some() {
+            return events;
+        }
+
+        // ターンを消費しない行動は、誰かが行動する前に弾く
+        if let Some(event) = check_actions(character, &actions, self.can_flee()) {
+            events.push(event);
+            return events;
+        }
+
+        let mut actions: Vec<Option<Action>> = actions.into_iter().map(Some).collect();
+        let mut defending = vec![false; character.party_size()];
+
+        for actor in self.turn_order(character) {
+            if self.result.is_some() {
+                break;
+            }
+            match actor {
+                Actor::Member(i) => {
+                    // このターンの途中で倒れたメンバーは行動しない
+                    if character.member(i).hp == 0 {
+                        continue;
+                    }
+                    let action = actions
+                        .get_mut(i)
+                        .and_then(Option::take)
+                        .unwrap_or(Action::Auto);
+                    self.member_turn(
+                        character,
+                        i,
+                        action,
+                        &mut defending,
+                        content,
+                        rng,
+                        &mut events,
+                    );
+                }
+                Actor::Enemy(j) => {
+                    // このターンの途中で倒されたモンスターは行動しない
+                    if !self.enemies[j].is_alive() {
+                        continue;
+                    }
+                    self.enemy_turn(character, j, &defending, content, rng, &mut events);
+                }
+            }
+        }
+
+        events
+    }
+
+    // パーティメンバー1人の行動
+    #[allow(clippy::too_many_arguments)]
+    fn member_turn(
+        &mut self,
+        character: &mut Character,
+        i: usize,
+        action: Action,
+        defending: &mut [bool],
+        content: &Content,
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        // 🎲 decide attack or recovery for random
+        let action = match action {
+            Action::Auto if rng.gen_bool(0.5) => {
+                let living = self.living_enemies();
+                Action::Attack(*living.choose(rng).expect("決着前なので必ず残っている"))
+            }
+            Action::Auto => Action::Recover,
+            other => other,
+        };
+
+        let bonus = equipment_bonus(character.member(i), &content.equipment);
+        let member = character.member_mut(i);
+        let blocked = status::start_turn(
+            &mut member.status,
+            &member.name,
+            &mut member.hp,
+            member.max_hp,
+            rng,
+            events,
+        );
+        if member.hp == 0 {
+            self.member_down(character, i, events);
+            return;
+        }
+
+        // 攻撃した相手（倒したかどうかを後で確認する）
+        let mut target = None;
+
+        if !blocked {
+            match action {
+                Action::Attack(j) => {
+                    let j = self.retarget(j);
+                    let attack = self.roll_attack(i, member, &bonus, rng);
+                    let name = member.name.clone();
+                    self.physical_hit(&name, j, bonus.element, attack, events);
+                    target = Some(j);
+                }
+                Action::Recover => {
+                    let recovery = rng
+                        .gen_range(member.stats.min_recovery..=member.stats.max_recovery)
+                        + bonus.recovery;
+                    heal(member, recovery);
+                    events.push(BattleEvent::Heal {
+                        actor: member.name.clone(),
+                        amount: recovery,
+                    });
+                }
+                Action::Defend => {
+                    defending[i] = true;
+                    events.push(BattleEvent::Defend {
+                        actor: member.name.clone(),
+                    });
+                }
+                Action::Flee => {
+                    if rng.gen_bool(self.flee_chance(character)) {
+                        events.push(BattleEvent::FleeSucceeded);
+                        self.result = Some(BattleResult::Escaped);
+                        return;
+                    }
+                    events.push(BattleEvent::FleeFailed);
+                }
+                Action::UseItem(item) => {
+                    // 所持品は主人公のものを使う
+                    character.inventory.remove(&item.id);
+                    let member = character.member_mut(i);
+                    events.push(BattleEvent::ItemUsed {
+                        actor: member.name.clone(),
+                        name: item.name.clone(),
+                    });
+
+                    match item.effect {
+                        ItemEffect::Heal { amount } => events.push(BattleEvent::Heal {
+                            actor: member.name.clone(),
+                            amount: heal(member, amount),
+                        }),
+                        ItemEffect::RestoreMp { amount } => events.push(BattleEvent::MpRestored {
+                            actor: member.name.clone(),
+                            amount: restore_mp(member, amount),
+                        }),
+                        ItemEffect::Escape => {
+                            events.push(BattleEvent::FleeSucceeded);
+                            self.result = Some(BattleResult::Escaped);
+                            return;
+                        }
+                        ItemEffect::AttackBuff { amount, turns } => {
+                            // 効果が切れるのは次のターンからなので1ターン分多く持たせる
+                            self.add_buff(i, BuffKind::Attack, amount, turns + 1);
+                            events.push(BattleEvent::AttackBuffed {
+                                target: member.name.clone(),
+                                amount,
+                                turns,
+                            });
+                        }
+                        ItemEffect::Cure { status } => {
+                            for status in status::cure(&mut member.status, status) {
+                                events.push(BattleEvent::StatusRecovered {
+                                    target: member.name.clone(),
+                                    status,
+                                });
+                            }
+                        }
+                    }
+                }
+                Action::Skill(skill, j) => {
+                    let j = self.retarget(j);
+                    if skill.effect.targets_enemy() {
+                        target = Some(j);
+                    }
+                    self.use_skill(member, i, skill, j, &bonus, rng, events);
+                }
+                Action::Auto => unreachable!(),
+            }
+        }
+
+        self.tick_buffs(character.member_mut(i), i, events);
+
+        if let Some(j) = target {
+            if !self.enemies[j].is_alive() {
+                self.defeat_enemy(character, j, content, rng, events);
+            }
+        }
+    }
+
+    // メンバーの継続回復を行い、一時的な効果の残りターンを減らす
+    fn tick_buffs(&mut self, member: &mut PartyMember, i: usize, events: &mut Vec<BattleEvent>) {
+        for buff in self.buffs.iter_mut().filter(|buff| buff.member == i) {
+            if buff.kind == BuffKind::Regen {
+                events.push(BattleEvent::Regenerated {
+                    target: member.name.clone(),
+                    amount: heal(member, buff.amount),
+                });
+            }
+            buff.turns -= 1;
+        }
+        self.buffs.retain(|buff| {
+            if buff.member == i && buff.turns == 0 {
+                events.push(BattleEvent::BuffExpired {
+                    target: member.name.clone(),
+                    buff: buff.kind,
+                });
+            }
+            buff.turns > 0
+        });
+    }
+
+    // スキルを使う（MPが足りることは確認済み、target は狙うモンスターの番号）
+    #[allow(clippy::too_many_arguments)]
+    fn use_skill(
+        &mut self,
+        member: &mut PartyMember,
+        i: usize,
+        skill: Skill,
+        target: usize,
+        bonus: &StatBonus,
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        member.mp -= skill.mp_cost;
+        events.push(BattleEvent::SkillUsed {
+            actor: member.name.clone(),
+            name: skill.name.clone(),
+            mp_cost: skill.mp_cost,
+        });
+
+        match skill.effect {
+            SkillEffect::MultiHit { hits } => {
+                for _ in 0..hits {
+                    let attack = self.roll_attack(i, member, bonus, rng);
+                    self.physical_hit(&member.name, target, bonus.element, attack, events);
+                    if !self.enemies[target].is_alive() {
+                        break;
+                    }
+                }
+            }
+            SkillEffect::HeavyStrike { power } => {
+                let attack = self.roll_attack(i, member, bonus, rng) * power / 100;
+                self.physical_hit(&member.name, target, bonus.element, attack, events);
+            }
+            SkillEffect::Spell {
+                element,
+                min_damage,
+                max_damage,
+            } => {
+                let damage = rng.gen_range(min_damage..=max_damage) + member.lv * 2;
+                self.spell_hit(target, element, damage, events);
+            }
+            SkillEffect::Regen { amount, turns } => {
+                // 使ったターンから回復が始まる
+                self.add_buff(i, BuffKind::Regen, amount, turns);
+                events.push(BattleEvent::RegenStarted {
+                    target: member.name.clone(),
+                    amount,
+                    turns,
+                });
+            }
+            SkillEffect::Buff {
+                attack,
+                defense,
+                turns,
+            } => {
+                if attack > 0 {
+                    self.add_buff(i, BuffKind::Attack, attack, turns + 1);
+                    events.push(BattleEvent::AttackBuffed {
+                        target: member.name.clone(),
+                        amount: attack,
+                        turns,
+                    });
+                }
+                if defense > 0 {
+                    self.add_buff(i, BuffKind::Defense, defense, turns + 1);
+                    events.push(BattleEvent::DefenseBuffed {
+                        target: member.name.clone(),
+                        amount: defense,
+                        turns,
+                    });
+                }
+            }
+            SkillEffect::Inflict(inflict) => {
+                let enemy = &mut self.enemies[target];
+                events.push(status::inflict(
+                    &mut enemy.status,
+                    &inflict,
+                    &enemy.monster.name,
+                    rng,
+                ));
+            }
+        }
+    }
+
+    // モンスター1体の行動
+    fn enemy_turn(
+        &mut self,
+        character: &mut Character,
+        j: usize,
+        defending: &[bool],
+        content: &Content,
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        let enemy = &mut self.enemies[j];
+        let blocked = status::start_turn(
+            &mut enemy.status,
+            &enemy.monster.name,
+            &mut enemy.hp,
+            enemy.monster.max_hp,
+            rng,
+            events,
+        );
+        if !enemy.is_alive() {
+            self.defeat_enemy(character, j, content, rng, events);
+            return;
+        }
+
+        if !blocked {
+            self.enemy_action(character, j, content, defending, rng, events);
+        }
+    }
+
+    // モンスターの行動パターンに当たった特技を使い、どれにも当たらなければ通常攻撃する
+    fn enemy_action(
+        &mut self,
+        character: &mut Character,
+        j: usize,
+        content: &Content,
+        defending: &[bool],
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        let enemy = &mut self.enemies[j];
+        enemy.turns += 1;
+
+        // 力をためている途中なら、ため終わるまで何もしない
+        if let Some((index, turns)) = enemy.charging {
+            let ability = enemy.monster.abilities[index].clone();
+            if turns > 1 {
+                enemy.charging = Some((index, turns - 1));
+                events.push(BattleEvent::Charging {
+                    monster: enemy.monster.name.clone(),
+                    ability: ability.name,
+                });
+            } else {
+                enemy.charging = None;
+                self.use_ability(character, j, content, &ability, defending, rng, events);
+            }
+            return;
+        }
+
+        let mut chosen = None;
+        for (i, rule) in enemy.monster.behavior.iter().enumerate() {
+            if rule.once && enemy.used.contains(&i) {
+                continue;
+            }
+            if rule.matches(enemy.hp, enemy.monster.max_hp, enemy.turns)
+                && rng.gen_bool(rule.chance)
+            {
+                chosen = Some(i);
+                break;
+            }
+        }
+
+        let Some(rule_index) = chosen else {
+            self.enemy_attack(character, j, content, 100, None, defending, rng, events);
+            return;
+        };
+        let rule = &enemy.monster.behavior[rule_index];
+        let index = enemy
+            .monster
+            .abilities
+            .iter()
+            .position(|ability| ability.id == rule.ability)
+            .expect("行動パターンの特技は読み込み時に確認済み");
+        if rule.once {
+            enemy.used.push(rule_index);
+        }
+        let ability = enemy.monster.abilities[index].clone();
+
+        if let AbilityEffect::Strike {
+            charge_turns: charge @ 1..,
+            ..
+        } = ability.effect
+        {
+            enemy.charging = Some((index, charge));
+            events.push(BattleEvent::Charging {
+                monster: enemy.monster.name.clone(),
+                ability: ability.name,
+            });
+            return;
+        }
+
+        self.use_ability(character, j, content, &ability, defending, rng, events);
+    }
+
+    // モンスターが特技を使う
+    #[allow(clippy::too_many_arguments)]
+    fn use_ability(
+        &mut self,
+        character: &mut Character,
+        j: usize,
+        content: &Content,
+        ability: &Ability,
+        defending: &[bool],
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        let name = self.enemies[j].monster.name.clone();
+
+        // 手下を呼べないときは通常攻撃に切り替える
+        let minion = match &ability.effect {
+            AbilityEffect::Summon { monster } => {
+                match content.monsters.iter().find(|m| m.name == *monster) {
+                    Some(minion) if self.living_enemies().len() < MAX_ENEMIES => {
+                        Some(minion.clone())
+                    }
+                    _ => {
+                        self.enemy_attack(character, j, content, 100, None, defending, rng, events);
+                        return;
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        events.push(BattleEvent::MonsterAbility {
+            monster: name.clone(),
+            ability: ability.name.clone(),
+        });
+
+        match &ability.effect {
+            AbilityEffect::Strike { power, inflict, .. } => {
+                self.enemy_attack(
+                    character,
+                    j,
+                    content,
+                    *power,
+                    inflict.as_ref(),
+                    defending,
+                    rng,
+                    events,
+                );
+            }
+            AbilityEffect::Heal { percent } => {
+                let enemy = &mut self.enemies[j];
+                let before = enemy.hp;
+                let amount = enemy.monster.max_hp * percent / 100;
+                enemy.hp = (enemy.hp + amount).min(enemy.monster.max_hp);
+                events.push(BattleEvent::MonsterHealed {
+                    monster: name,
+                    amount: enemy.hp - before,
+                });
+            }
+            AbilityEffect::Summon { .. } => {
+                let minion = minion.expect("手下は上で決めている");
+                // 同じ種類が既にいれば、出てきた順に B, C, ... をつける
+                let same = self
+                    .enemies
+                    .iter()
+                    .filter(|e| e.species == minion.name)
+                    .count();
+                let suffix = if same > 0 {
+                    enemy_suffix(same)
+                } else {
+                    String::new()
+                };
+                let minion = Enemy::new(minion, suffix);
+                events.push(BattleEvent::Summoned {
+                    monster: name,
+                    minion: minion.monster.name.clone(),
+                });
+                events.push(BattleEvent::Encounter {
+                    name: minion.monster.name.clone(),
+                    hp: minion.hp,
+                });
+                self.enemies.push(minion);
+            }
+            AbilityEffect::Enrage { attack } => {
+                self.enemies[j].rage += attack;
+                events.push(BattleEvent::Enraged {
+                    monster: name,
+                    attack: *attack,
+                });
+            }
+        }
+    }
+
+    // モンスターの攻撃（power は通常攻撃に対する威力%）
+    #[allow(clippy::too_many_arguments)]
+    fn enemy_attack(
+        &mut self,
+        character: &mut Character,
+        j: usize,
+        content: &Content,
+        power: u32,
+        inflict: Option<&status::Inflict>,
+        defending: &[bool],
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        // 狙うメンバーはランダムに決める
+        let Some(&i) = character.living_members().choose(rng) else {
+            return;
+        };
+        let defense = equipment_bonus(character.member(i), &content.equipment).defense
+            + self.buff_amount(i, BuffKind::Defense);
+        let enemy = &self.enemies[j];
+        let monster = &enemy.monster;
+        let target = character.member_mut(i);
+
+        let mut attack =
+            (rng.gen_range(monster.min_attack..=monster.max_attack) + enemy.rage) * power / 100;
+        // 防御力でダメージを減らす（ただし最低1ダメージは受ける）
+        if attack > 0 {
+            attack = attack.saturating_sub(defense).max(1);
+        }
+        if defending[i] {
+            attack /= 2;
+        }
+        target.hp = target.hp.saturating_sub(attack);
+        events.push(BattleEvent::DamageTaken {
+            monster: monster.name.clone(),
+            target: target.name.clone(),
+            damage: attack,
+        });
+
+        // 攻撃が当たれば状態異常の付与を判定する（かからなかった場合は表示しない）
+        if attack > 0 && target.hp > 0 {
+            for inflict in monster.inflicts.iter().chain(inflict) {
+                let event = status::inflict(&mut target.status, inflict, &target.name, rng);
+                if matches!(event, BattleEvent::StatusInflicted { .. }) {
+                    events.push(event);
+                }
+            }
+        }
+
+        if target.hp == 0 {
+            self.member_down(character, i, events);
+        }
+    }
+
+    // HPが0になったモンスターの報酬とレベルアップ
+    // 次の形態が残っていれば変身するだけで、全員倒せば勝利となる
+    fn defeat_enemy(
+        &mut self,
+        character: &mut Character,
+        j: usize,
+        content: &Content,
+        rng: &mut GameRng,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        if let Some(event) = self.enemies[j].transform() {
+            events.push(event);
+            return;
+        }
+
+        let monster = &self.enemies[j].monster;
+        events.push(BattleEvent::MonsterDefeated {
+            name: monster.name.clone(),
+        });
+        events.push(BattleEvent::ExpGained { exp: monster.exp });
+
+        let loot = roll_loot(monster, rng);
+        if loot.gold > 0 {
+            character.gold += loot.gold;
+            events.push(BattleEvent::GoldGained { gold: loot.gold });
+        }
+        for drop in loot.items {
+            character.inventory.add(&drop.item, drop.count);
+            events.push(BattleEvent::ItemDropped {
+                item: drop.item,
+                count: drop.count,
+            });
+        }
+
+        // 経験値は倒れていないメンバーだけが得る（仲間はレベルアップで新しいスキルを覚えない）
+        // 倒れたメンバーがレベルアップでHPを回復して起き上がらないようにする
+        for i in character.living_members() {
+            let skills: &[Skill] = if i == 0 { &content.skills } else { &[] };
+            let member = character.member_mut(i);
+            member.exp += monster.exp;
+            for level_up in check_level_up(member, skills, rng) {
+                events.push(BattleEvent::LevelUp(level_up));
+            }
+        }
+
+        if !self.living_enemies().is_empty() {
+            return;
+        }
+
+        // 勝利すると倒れたメンバーも含めて全員のHP・MPが回復する
+        for i in 0..character.party_size() {
+            let member = character.member_mut(i);
+            member.hp = member.max_hp;
+            member.mp = member.max_mp;
+        }
+        self.result = Some(BattleResult::Win);
+    }
+
+    // メンバーが倒れた。全員倒れたら敗北
+    fn member_down(&mut self, character: &Character, i: usize, events: &mut Vec<BattleEvent>) {
+        events.push(BattleEvent::MemberDown {
+            name: character.member(i).name.clone(),
+        });
+        self.buffs.retain(|buff| buff.member != i);
+
+        if character.living_members().is_empty() {
+            events.push(BattleEvent::Defeat);
+            self.result = Some(BattleResult::Lose);
+        }
+    }
+}
+
+// 同じ種類のモンスターを見分ける記号（0 → A, 1 → B, ...）
+fn enemy_suffix(index: usize) -> String {
+    char::from(b'A' + (index % 26) as u8).to_string()
+}
+
+// ターンを消費しない行動（持っていないアイテム、MP不足のスキル、ボス戦での逃走）が無いか確認する
+fn check_actions(character: &Character, actions: &[Action], can_flee: bool) -> Option<BattleEvent> {
+    for (i, action) in actions.iter().enumerate() {
+        match action {
+            Action::Flee if !can_flee => return Some(BattleEvent::CannotFlee),
+            Action::UseItem(item) if !can_flee && item.effect == ItemEffect::Escape => {
+                return Some(BattleEvent::CannotFlee);
+            }
+            Action::UseItem(item) => {
+                // 同じアイテムを複数のメンバーが使う場合は、その個数だけ必要
+                let needed = actions[..=i]
+                    .iter()
+                    .filter(|a| matches!(a, Action::UseItem(other) if other.id == item.id))
+                    .count() as u32;
+                if character.inventory.count(&item.id) < needed {
+                    return Some(BattleEvent::NoItem);
+                }
+            }
+            Action::Skill(skill, _) if character.member(i).mp < skill.mp_cost => {
+                return Some(BattleEvent::NotEnoughMp {
+                    name: skill.name.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+    None
+}
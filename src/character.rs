@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    defeat::DefeatRules,
+    equipment::Equipped,
+    item::Inventory,
+    skill::{learn_skills, Skill},
+    status::{cure, StatusEffect, StatusKind},
+    GameRng,
+};
+
+// 新しいキャラクターのMP
+fn default_mp() -> u32 {
+    10
+}
+
+// 新しいキャラクターの素早さ（モンスターの speed を省略したときも同じ値）
+pub(crate) fn default_speed() -> u32 {
+    5
+}
+
+// パーティの最大人数（主人公を含む）
+pub const MAX_PARTY_SIZE: usize = 4;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Stats {
+    pub min_attack: u32,
+    pub max_attack: u32,
+    pub min_recovery: u32,
+    pub max_recovery: u32,
+    // 素早さ（高い順にバトルで行動する。逃げられる確率にも影響する）
+    pub speed: u32,
+}
+
+// パーティのメンバー1人分の項目（主人公と仲間で共通）
+// 項目を増やしたら、save.rs の MIGRATIONS で fill_missing_members を使って補う
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PartyMember {
+    pub name: String,
+    pub lv: u32,
+    pub hp: u32,
+    pub max_hp: u32,
+    pub stats: Stats,
+    pub exp: u32,
+    pub mp: u32,
+    pub max_mp: u32,
+    // 装備中の武器・防具・アクセサリー
+    pub equipment: Equipped,
+    // 覚えたスキルのID
+    pub skills: Vec<String>,
+    // かかっている状態異常（バトルが終わっても残る）
+    pub status: Vec<StatusEffect>,
+}
+
+// キャラクター構造体
+// 項目を増やしたら、古いセーブを変換する関数を save.rs の MIGRATIONS に足す
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Character {
+    // 主人公自身の項目（セーブでは他の項目と同じ階層に並ぶ）
+    #[serde(flatten)]
+    pub hero: PartyMember,
+    // クリア済みステージ番号のリスト
+    pub stages_cleared: Vec<u32>,
+    // 現在挑戦中のステージ（未選択時はNone）
+    pub current_stage: Option<u32>,
+    // 最後のプレイで使った乱数シード（--seed に渡せば同じ展開を再現できる）
+    pub seed: Option<u64>,
+    // 所持品
+    pub inventory: Inventory,
+    // 所持ゴールド
+    pub gold: u32,
+    // 在庫に限りのある商品の残り（アイテムID → 個数、買ったことの無い商品は shop.yaml の在庫のまま）
+    // ステージをクリアすると空に戻り、在庫が補充される
+    pub shop_stock: BTreeMap<String, u32>,
+    // 一緒に戦う仲間（所持品・ゴールドは主人公のものを共有する）
+    pub companions: Vec<PartyMember>,
+    // 全滅したときのペナルティ（主人公のものを使う）
+    pub defeat_rules: DefeatRules,
+    // ハードコアで全滅した（ロックされたセーブは遊べない）
+    pub game_over: bool,
+}
+
+// レベルアップ1回分の上昇値
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LevelUp {
+    pub name: String, // レベルアップしたメンバーの名前
+    pub lv: u32,
+    pub hp_increase: u32,
+    pub attack_increase: u32,
+    pub recovery_increase: u32,
+    pub mp_increase: u32,
+    pub speed_increase: u32,
+    // このレベルで覚えたスキルのID
+    pub learned: Vec<String>,
+}
+
+impl Character {
+    // Lv1の新しいキャラクターを作成する
+    // 初期アイテムとしてポーションを3個持たせる
+    pub fn new(name: String) -> Self {
+        let mut inventory = Inventory::default();
+        inventory.add("potion", 3);
+
+        Character {
+            hero: PartyMember {
+                name,
+                lv: 1,
+                hp: 50,
+                max_hp: 50,
+                stats: Stats {
+                    min_attack: 2,
+                    max_attack: 5,
+                    min_recovery: 1,
+                    max_recovery: 3,
+                    speed: default_speed(),
+                },
+                exp: 0,
+                mp: default_mp(),
+                max_mp: default_mp(),
+                equipment: Equipped::default(),
+                skills: Vec::new(),
+                status: Vec::new(),
+            },
+            stages_cleared: Vec::new(),
+            current_stage: None,
+            seed: None,
+            inventory,
+            gold: 0,
+            shop_stock: BTreeMap::new(),
+            companions: Vec::new(),
+            defeat_rules: DefeatRules::default(),
+            game_over: false,
+        }
+    }
+
+    // 主人公を含むパーティの人数
+    pub fn party_size(&self) -> usize {
+        1 + self.companions.len()
+    }
+
+    // パーティの i 番目のメンバー（0 は主人公）
+    pub fn member(&self, i: usize) -> &PartyMember {
+        if i == 0 {
+            &self.hero
+        } else {
+            &self.companions[i - 1]
+        }
+    }
+
+    pub fn member_mut(&mut self, i: usize) -> &mut PartyMember {
+        if i == 0 {
+            &mut self.hero
+        } else {
+            &mut self.companions[i - 1]
+        }
+    }
+
+    // HPが残っているメンバーの番号
+    pub fn living_members(&self) -> Vec<usize> {
+        (0..self.party_size())
+            .filter(|&i| self.member(i).hp > 0)
+            .collect()
+    }
+
+    // パーティ全員が休んでHP・MPを全回復し、状態異常をすべて治す
+    // 治った (メンバー名, 状態異常) を返す
+    pub fn rest(&mut self) -> Vec<(String, StatusKind)> {
+        let mut cured = Vec::new();
+        for i in 0..self.party_size() {
+            let member = self.member_mut(i);
+            member.hp = member.max_hp;
+            member.mp = member.max_mp;
+            for status in cure(&mut member.status, None) {
+                cured.push((member.name.clone(), status));
+            }
+        }
+        cured
+    }
+
+    // クリア済みステージに追加（重複チェック）
+    pub fn mark_stage_cleared(&mut self, stage_num: u32) {
+        if !self.stages_cleared.contains(&stage_num) {
+            self.stages_cleared.push(stage_num);
+        }
+    }
+}
+
+// 次のレベルに必要な経験値を計算する（ドラクエII風）
+// 計算式: floor(10 * lv^1.5 + 10 * lv)
+// 例: Lv1→2: 20, Lv2→3: 48, Lv10→11: 416, Lv40→41: 2930
+pub fn required_exp_to_level_up(current_lv: u32) -> u32 {
+    let lv = current_lv as f64;
+    (10.0 * lv.powf(1.5) + 10.0 * lv).floor() as u32
+}
+
+// 経験値が足りている限りレベルアップし、上昇値と覚えたスキルを順に返す
+pub fn check_level_up(
+    member: &mut PartyMember,
+    skills: &[Skill],
+    rng: &mut GameRng,
+) -> Vec<LevelUp> {
+    let mut level_ups = Vec::new();
+
+    while member.exp >= required_exp_to_level_up(member.lv) {
+        member.exp -= required_exp_to_level_up(member.lv);
+        member.lv += 1;
+
+        let hp_increase = rng.gen_range(5..=10);
+        member.max_hp += hp_increase;
+        member.hp = member.max_hp;
+
+        let attack_increase = rng.gen_range(1..=3);
+        member.stats.min_attack += attack_increase;
+        member.stats.max_attack += attack_increase;
+
+        let recovery_increase = rng.gen_range(1..=3);
+        member.stats.min_recovery += recovery_increase;
+        member.stats.max_recovery += recovery_increase;
+
+        let mp_increase = rng.gen_range(2..=4);
+        member.max_mp += mp_increase;
+        member.mp = member.max_mp;
+
+        let speed_increase = rng.gen_range(0..=1);
+        member.stats.speed += speed_increase;
+
+        level_ups.push(LevelUp {
+            name: member.name.clone(),
+            lv: member.lv,
+            hp_increase,
+            attack_increase,
+            recovery_increase,
+            mp_increase,
+            speed_increase,
+            learned: learn_skills(member, skills),
+        });
+    }
+
+    level_ups
+}
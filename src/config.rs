@@ -0,0 +1,48 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{find_key_line, load_checked, GameError, Result},
+    stage::{validate_encounter, EncounterRule},
+    timing::GameSpeed,
+};
+
+// 設定ファイルのパス
+pub const CONFIG_PATH: &str = "config.yaml";
+
+// ゲームの設定（config.yaml）
+// 項目が無い場合はデフォルト値を使う
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Config {
+    #[serde(default)]
+    pub speed: GameSpeed,
+    // 通常モード・ブーストモードの出現しやすさの上書き（モンスター名 → 重み）
+    #[serde(default)]
+    pub encounter_weights: BTreeMap<String, u32>,
+    // 通常モード・ブーストモードの群れの組み方（省略すると1体ずつ現れる）
+    #[serde(default)]
+    pub encounter: EncounterRule,
+}
+
+// 設定ファイルを読み込む（無ければデフォルト設定）
+pub fn load_config() -> Result<Config> {
+    let path = Path::new(CONFIG_PATH);
+
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    load_checked(CONFIG_PATH, check_config)
+}
+
+// 群れの組み方を確認する
+pub(crate) fn check_config(data: &str, config: &Config) -> Vec<GameError> {
+    match validate_encounter(&config.encounter) {
+        Ok(()) => Vec::new(),
+        Err(message) => {
+            vec![GameError::validation(CONFIG_PATH, message)
+                .at_line(find_key_line(data, "encounter"))]
+        }
+    }
+}
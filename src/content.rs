@@ -0,0 +1,61 @@
+use crate::{
+    equipment::{find_equipment, load_equipment, Equipment},
+    error::Result,
+    item::{find_item, load_items, Item},
+    monster::{load_monsters, Monster},
+    party::{load_companions, CompanionDef},
+    shop::{load_shop, ShopEntry},
+    skill::{load_skills, Skill},
+    stage::{load_stages, Stage},
+};
+
+// ゲームで使うデータファイル一式
+pub struct Content {
+    pub monsters: Vec<Monster>,
+    pub items: Vec<Item>,
+    pub equipment: Vec<Equipment>,
+    pub shop: Vec<ShopEntry>,
+    pub skills: Vec<Skill>,
+    pub companions: Vec<CompanionDef>,
+    pub stages: Vec<Stage>,
+}
+
+impl Content {
+    // すべてのデータファイルを読み込む
+    // ステージはボスを確認するため、モンスターの後に読み込む
+    pub fn load() -> Result<Content> {
+        let monsters = load_monsters()?;
+        let stages = load_stages(&monsters)?;
+        Ok(Content {
+            monsters,
+            items: load_items()?,
+            equipment: load_equipment()?,
+            shop: load_shop()?,
+            skills: load_skills()?,
+            companions: load_companions()?,
+            stages,
+        })
+    }
+
+    // アイテムIDまたは装備IDから値段を返す（見つからなければ0）
+    pub fn price_of(&self, id: &str) -> u32 {
+        if let Some(item) = find_item(&self.items, id) {
+            item.price
+        } else if let Some(gear) = find_equipment(&self.equipment, id) {
+            gear.price
+        } else {
+            0
+        }
+    }
+
+    // アイテムIDまたは装備IDから表示名を返す（見つからなければIDのまま）
+    pub fn name_of<'a>(&'a self, id: &'a str) -> &'a str {
+        if let Some(item) = find_item(&self.items, id) {
+            &item.name
+        } else if let Some(gear) = find_equipment(&self.equipment, id) {
+            &gear.name
+        } else {
+            id
+        }
+    }
+}
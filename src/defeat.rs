@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{battle::BattleEvent, character::Character};
+
+// 全滅したあとに復活する場所
+// Town: 通常モードの探索とステージの挑戦はそこで終わる（ブーストモードは次のバトルへ進む）
+// Spot: 通常モードの探索とステージの通常バトルはそのまま続く（ボスに敗れたらステージを出る）
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Respawn {
+    #[default]
+    Town, // 町に戻される
+    Spot, // その場で起き上がる
+}
+
+// ハードコアで全滅したときのセーブの扱い
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Hardcore {
+    Delete, // セーブを削除する
+    Lock,   // セーブを残すが、二度と遊べなくする
+}
+
+// 全滅したときのペナルティ（キャラクター作成時に選び、セーブに記録する）
+// 互換性のため、項目が無ければペナルティ無しで町に戻される
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DefeatRules {
+    // 失う所持ゴールドの割合 (%)
+    #[serde(default)]
+    pub gold_loss: u32,
+    // 次のレベルに向けて貯めた経験値を失う割合 (%)
+    #[serde(default)]
+    pub exp_loss: u32,
+    #[serde(default)]
+    pub respawn: Respawn,
+    // ハードコアモード（全滅するとゲームオーバー）
+    #[serde(default)]
+    pub hardcore: Option<Hardcore>,
+}
+
+impl DefeatRules {
+    // 全滅してもその場で起き上がり、探索を続けられるか（ハードコアでは続けられない）
+    pub fn revives_on_spot(&self) -> bool {
+        self.hardcore.is_none() && self.respawn == Respawn::Spot
+    }
+
+    // キャラクター作成時に選べるルールの一覧（名前, 説明, ルール）
+    pub fn presets() -> Vec<(&'static str, &'static str, DefeatRules)> {
+        vec![
+            (
+                "やさしい",
+                "ペナルティ無し。その場で起き上がって探索を続ける（ボスに敗れたときはステージを出る）",
+                DefeatRules {
+                    gold_loss: 0,
+                    exp_loss: 0,
+                    respawn: Respawn::Spot,
+                    hardcore: None,
+                },
+            ),
+            (
+                "ふつう",
+                "所持ゴールドの半分を失い、町に戻される",
+                DefeatRules {
+                    gold_loss: 50,
+                    exp_loss: 0,
+                    respawn: Respawn::Town,
+                    hardcore: None,
+                },
+            ),
+            (
+                "きびしい",
+                "所持ゴールドの半分と、次のレベルに向けて貯めた経験値を失い、町に戻される",
+                DefeatRules {
+                    gold_loss: 50,
+                    exp_loss: 100,
+                    respawn: Respawn::Town,
+                    hardcore: None,
+                },
+            ),
+            (
+                "ハードコア",
+                "全滅するとセーブが削除される",
+                DefeatRules {
+                    gold_loss: 0,
+                    exp_loss: 0,
+                    respawn: Respawn::Town,
+                    hardcore: Some(Hardcore::Delete),
+                },
+            ),
+            (
+                "ハードコア（記録を残す）",
+                "全滅するとセーブがロックされ、二度と遊べなくなる",
+                DefeatRules {
+                    gold_loss: 0,
+                    exp_loss: 0,
+                    respawn: Respawn::Town,
+                    hardcore: Some(Hardcore::Lock),
+                },
+            ),
+        ]
+    }
+}
+
+// 全滅したときにキャラクターのルールに従ってペナルティを与え、起きた出来事を返す
+// ハードコアならゲームオーバーにし、それ以外はパーティ全員を復活させる
+pub fn apply_defeat(character: &mut Character) -> Vec<BattleEvent> {
+    let rules = character.defeat_rules;
+    let mut events = Vec::new();
+
+    if let Some(hardcore) = rules.hardcore {
+        character.game_over = true;
+        events.push(BattleEvent::GameOver { hardcore });
+        return events;
+    }
+
+    let gold = character.gold * rules.gold_loss.min(100) / 100;
+    if gold > 0 {
+        character.gold -= gold;
+        events.push(BattleEvent::GoldLost { gold });
+    }
+
+    for i in 0..character.party_size() {
+        let member = character.member_mut(i);
+        let exp = member.exp * rules.exp_loss.min(100) / 100;
+        if exp > 0 {
+            member.exp -= exp;
+            events.push(BattleEvent::ExpLost {
+                name: member.name.clone(),
+                exp,
+            });
+        }
+    }
+
+    // 復活すると全員のHP・MPが回復し、状態異常も治る
+    character.rest();
+    events.push(BattleEvent::Revived {
+        respawn: rules.respawn,
+    });
+
+    events
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+// 魔法や武器、モンスターの属性
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Element {
+    Fire,    // 炎
+    Ice,     // 氷
+    Thunder, // 雷
+    Holy,    // 聖
+    Dark,    // 闇
+}
+
+impl Element {
+    // 属性の日本語名を返す
+    pub fn name(&self) -> &'static str {
+        match self {
+            Element::Fire => "炎",
+            Element::Ice => "氷",
+            Element::Thunder => "雷",
+            Element::Holy => "聖",
+            Element::Dark => "闇",
+        }
+    }
+}
+
+// 攻撃の属性と相手との相性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Affinity {
+    Normal,    // 普通
+    Effective, // 弱点を突いた（1.5倍）
+    Resisted,  // 耐性がある（半減、ただし最低1ダメージ）
+}
+
+impl Affinity {
+    // 相性に応じてダメージを補正する
+    pub fn apply(&self, damage: u32) -> u32 {
+        match self {
+            Affinity::Normal => damage,
+            Affinity::Effective => damage * 3 / 2,
+            Affinity::Resisted if damage == 0 => 0,
+            Affinity::Resisted => (damage / 2).max(1),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::{fmt, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    character::{Character, PartyMember},
+    element::Element,
+    error::{entry_line, load_list, GameError, Result},
+};
+
+// 装備データのパス
+pub const EQUIPMENT_PATH: &str = "equipment.yaml";
+
+// 装備する部位
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EquipSlot {
+    Weapon,    // 武器
+    Armor,     // 防具
+    Accessory, // アクセサリー
+}
+
+impl EquipSlot {
+    // 部位の日本語名を返す
+    pub fn name(&self) -> &'static str {
+        match self {
+            EquipSlot::Weapon => "武器",
+            EquipSlot::Armor => "防具",
+            EquipSlot::Accessory => "アクセサリー",
+        }
+    }
+
+    pub fn all() -> [EquipSlot; 3] {
+        [EquipSlot::Weapon, EquipSlot::Armor, EquipSlot::Accessory]
+    }
+}
+
+// 装備品
+// attack と recovery は Stats の最小値・最大値の両方に加算される
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Equipment {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub slot: EquipSlot,
+    #[serde(default)]
+    pub attack: u32,
+    #[serde(default)]
+    pub defense: u32,
+    #[serde(default)]
+    pub recovery: u32,
+    // 武器の属性（通常攻撃とスキルの物理攻撃に乗る）
+    #[serde(default)]
+    pub element: Option<Element>,
+    // 値段（ショップでの売値はこの半額。0 なら売れない）
+    #[serde(default)]
+    pub price: u32,
+}
+
+// 装備中のアイテムID
+// 外した装備や未装備の装備品は Character の inventory に入る
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Equipped {
+    #[serde(default)]
+    pub weapon: Option<String>,
+    #[serde(default)]
+    pub armor: Option<String>,
+    #[serde(default)]
+    pub accessory: Option<String>,
+}
+
+impl Equipped {
+    pub fn get(&self, slot: EquipSlot) -> Option<&str> {
+        match slot {
+            EquipSlot::Weapon => self.weapon.as_deref(),
+            EquipSlot::Armor => self.armor.as_deref(),
+            EquipSlot::Accessory => self.accessory.as_deref(),
+        }
+    }
+
+    fn slot_mut(&mut self, slot: EquipSlot) -> &mut Option<String> {
+        match slot {
+            EquipSlot::Weapon => &mut self.weapon,
+            EquipSlot::Armor => &mut self.armor,
+            EquipSlot::Accessory => &mut self.accessory,
+        }
+    }
+}
+
+// 装備による能力値の上昇分
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatBonus {
+    pub attack: u32,
+    pub defense: u32,
+    pub recovery: u32,
+    // 装備している武器の属性
+    pub element: Option<Element>,
+}
+
+// 装備できなかった理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EquipError {
+    NotOwned, // その装備を持っていない
+}
+
+impl fmt::Display for EquipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EquipError::NotOwned => write!(f, "その装備を持っていません"),
+        }
+    }
+}
+
+pub fn load_equipment() -> Result<Vec<Equipment>> {
+    load_list(EQUIPMENT_PATH, check_equipment)
+}
+
+// 装備IDの重複と、武器以外に属性が付いていないかを確認する
+pub(crate) fn check_equipment(data: &str, equipment: &[Equipment]) -> Vec<GameError> {
+    let path = Path::new(EQUIPMENT_PATH);
+    let mut problems = Vec::new();
+
+    for (i, gear) in equipment.iter().enumerate() {
+        if equipment[..i].iter().any(|other| other.id == gear.id) {
+            problems.push(
+                GameError::validation(path, format!("装備ID {} が重複しています", gear.id))
+                    .at_line(entry_line(data, i)),
+            );
+        }
+        if gear.element.is_some() && gear.slot != EquipSlot::Weapon {
+            problems.push(
+                GameError::validation(
+                    path,
+                    format!("{}: element を指定できるのは武器だけです", gear.id),
+                )
+                .at_line(entry_line(data, i)),
+            );
+        }
+    }
+
+    problems
+}
+
+// IDから装備品を探す
+pub fn find_equipment<'a>(equipment: &'a [Equipment], id: &str) -> Option<&'a Equipment> {
+    equipment.iter().find(|gear| gear.id == id)
+}
+
+// 装備中の装備品による能力値の上昇分を合計する
+// 属性は武器のものだけを使う
+pub fn equipment_bonus(member: &PartyMember, equipment: &[Equipment]) -> StatBonus {
+    let bonus = EquipSlot::all()
+        .iter()
+        .filter_map(|slot| member.equipment.get(*slot))
+        .filter_map(|id| find_equipment(equipment, id))
+        .fold(StatBonus::default(), |bonus, gear| StatBonus {
+            attack: bonus.attack + gear.attack,
+            defense: bonus.defense + gear.defense,
+            recovery: bonus.recovery + gear.recovery,
+            element: None,
+        });
+
+    StatBonus {
+        element: member
+            .equipment
+            .get(EquipSlot::Weapon)
+            .and_then(|id| find_equipment(equipment, id))
+            .and_then(|weapon| weapon.element),
+        ..bonus
+    }
+}
+
+// 所持品からメンバー（0 は主人公）に装備させる。同じ部位の装備は外して所持品に戻す
+// 所持品はパーティで共有している
+pub fn equip(
+    character: &mut Character,
+    member: usize,
+    gear: &Equipment,
+) -> std::result::Result<(), EquipError> {
+    if !character.inventory.remove(&gear.id) {
+        return Err(EquipError::NotOwned);
+    }
+
+    if let Some(previous) = character
+        .member_mut(member)
+        .equipment
+        .slot_mut(gear.slot)
+        .replace(gear.id.clone())
+    {
+        character.inventory.add(&previous, 1);
+    }
+    Ok(())
+}
+
+// メンバーの部位の装備を外して所持品に戻し、外した装備のIDを返す
+pub fn unequip(character: &mut Character, member: usize, slot: EquipSlot) -> Option<String> {
+    let removed = character
+        .member_mut(member)
+        .equipment
+        .slot_mut(slot)
+        .take()?;
+    character.inventory.add(&removed, 1);
+    Some(removed)
+}
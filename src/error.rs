@@ -0,0 +1,250 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+
+// ゲームデータの読み書きで起きるエラー
+// どのファイルの何行目が原因かを表示できるよう、パスと位置を持つ
+#[derive(Debug)]
+pub enum GameError {
+    // ファイルの読み書きに失敗した
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    // YAML の解析に失敗した（位置が分かれば行・列を持つ）
+    Parse {
+        path: PathBuf,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+    // 解析はできたが内容が不正（位置が分かれば行を持つ）
+    Validation {
+        path: PathBuf,
+        line: Option<usize>,
+        message: String,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, GameError>;
+
+impl GameError {
+    pub fn io(path: impl AsRef<Path>, source: io::Error) -> Self {
+        GameError::Io {
+            path: path.as_ref().to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn parse(path: impl AsRef<Path>, error: serde_yaml::Error) -> Self {
+        let location = error.location();
+        GameError::Parse {
+            path: path.as_ref().to_path_buf(),
+            line: location.as_ref().map(|l| l.line()),
+            column: location.as_ref().map(|l| l.column()),
+            message: error.to_string(),
+        }
+    }
+
+    pub fn validation(path: impl AsRef<Path>, message: impl Into<String>) -> Self {
+        GameError::Validation {
+            path: path.as_ref().to_path_buf(),
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    // 検証エラーに行番号を付ける
+    pub fn at_line(self, line: Option<usize>) -> Self {
+        match self {
+            GameError::Validation { path, message, .. } => GameError::Validation {
+                path,
+                line,
+                message,
+            },
+            other => other,
+        }
+    }
+
+    // エラーの原因となったファイル
+    pub fn path(&self) -> &Path {
+        match self {
+            GameError::Io { path, .. }
+            | GameError::Parse { path, .. }
+            | GameError::Validation { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::Io { path, source } => {
+                write!(f, "{}: 読み書きに失敗しました: {}", path.display(), source)
+            }
+            GameError::Parse {
+                path,
+                line: Some(line),
+                column: Some(column),
+                message,
+            } => write!(
+                f,
+                "{}:{}:{}: 解析に失敗しました: {}",
+                path.display(),
+                line,
+                column,
+                message
+            ),
+            GameError::Parse { path, message, .. } => {
+                write!(f, "{}: 解析に失敗しました: {}", path.display(), message)
+            }
+            GameError::Validation {
+                path,
+                line: Some(line),
+                message,
+            } => write!(
+                f,
+                "{}:{}: 不正なデータです: {}",
+                path.display(),
+                line,
+                message
+            ),
+            GameError::Validation { path, message, .. } => {
+                write!(f, "{}: 不正なデータです: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GameError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// データファイルを読み込んで解析し、元の文字列と一緒に返す
+pub(crate) fn read_data<T: DeserializeOwned>(file: &str) -> Result<(String, T)> {
+    let path = Path::new(file);
+    let data = fs::read_to_string(path).map_err(|e| GameError::io(path, e))?;
+    let value = serde_yaml::from_str(&data).map_err(|e| GameError::parse(path, e))?;
+    Ok((data, value))
+}
+
+// データファイルを読み込み、check が見つけた最初の問題をエラーとして返す
+// check は問題をすべて集める関数で、kt_rpg validate でも同じものを使う
+pub(crate) fn load_checked<T: DeserializeOwned>(
+    file: &str,
+    check: impl FnOnce(&str, &T) -> Vec<GameError>,
+) -> Result<T> {
+    let (data, value) = read_data(file)?;
+    match check(&data, &value).into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(value),
+    }
+}
+
+// 一覧のデータファイル（monsters.yaml など）を読み込む
+pub(crate) fn load_list<T: DeserializeOwned>(
+    file: &str,
+    check: impl FnOnce(&str, &[T]) -> Vec<GameError>,
+) -> Result<Vec<T>> {
+    load_checked(file, |data, list: &Vec<T>| check(data, list))
+}
+
+// 一番外側の一覧の index 番目（0始まり）の項目が始まる行番号（1始まり）を返す
+// 解析後の値から元の YAML の位置を探すのに使う
+// 項目は最初の "-" と同じ字下げの "-" で始まるものとして数える（[...] で書かれた一覧にも対応する）
+pub fn entry_line(data: &str, index: usize) -> Option<usize> {
+    let lines = data.lines().enumerate().filter(|(_, line)| {
+        let line = line.trim_start();
+        !line.is_empty() && !line.starts_with('#') && !line.starts_with("---")
+    });
+    let (_, first) = lines.clone().next()?;
+    if first.trim_start().starts_with('[') {
+        return flow_entry_line(data, index);
+    }
+
+    let indent = first.len() - first.trim_start().len();
+    lines
+        .filter(|(_, line)| {
+            let item = line.trim_start();
+            line.len() - item.len() == indent && (item == "-" || item.starts_with("- "))
+        })
+        .nth(index)
+        .map(|(i, _)| i + 1)
+}
+
+// [...] で書かれた一覧の index 番目の項目が始まる行番号を返す
+// 括弧の深さを数え、一番外側の "[" と "," の直後にある文字を項目の始まりとする
+fn flow_entry_line(data: &str, index: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut found = 0;
+    let mut expecting = false;
+
+    for (n, line) in data.lines().enumerate() {
+        let mut escaped = false;
+        let mut after_space = true;
+        for c in line.chars() {
+            // 引用符の中の括弧やカンマは数えない
+            if let Some(q) = quote {
+                if escaped {
+                    escaped = false;
+                } else if q == '"' && c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+                continue;
+            }
+            if c.is_whitespace() {
+                after_space = true;
+                continue;
+            }
+            // 空白の後の # から行末まではコメント
+            if c == '#' && after_space {
+                break;
+            }
+            after_space = false;
+
+            if depth == 1 && expecting && c != ',' && c != ']' {
+                if found == index {
+                    return Some(n + 1);
+                }
+                found += 1;
+                expecting = false;
+            }
+            match c {
+                '"' | '\'' => quote = Some(c),
+                '[' | '{' => {
+                    depth += 1;
+                    expecting = depth == 1;
+                }
+                ']' | '}' => depth = depth.saturating_sub(1),
+                ',' if depth == 1 => expecting = true,
+                _ => {}
+            }
+        }
+    }
+
+    None
+}
+
+// マップのキー key が書かれた行番号（1始まり）を返す（キーは引用符で囲まれていてもよい）
+pub fn find_key_line(data: &str, key: &str) -> Option<usize> {
+    let quoted = format!("\"{}\"", key);
+    data.lines()
+        .position(|line| {
+            let line = line.trim_start();
+            line.strip_prefix(quoted.as_str())
+                .or_else(|| line.strip_prefix(key))
+                .is_some_and(|rest| rest.trim_start().starts_with(':'))
+        })
+        .map(|i| i + 1)
+}
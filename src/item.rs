@@ -0,0 +1,185 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    battle::BattleEvent,
+    character::{Character, PartyMember},
+    error::{entry_line, load_list, GameError, Result},
+    status::{cure, StatusKind},
+};
+
+// アイテムデータのパス
+pub const ITEMS_PATH: &str = "items.yaml";
+
+// アイテムの効果
+// YAML では type フィールドで種類を指定する
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ItemEffect {
+    // HPを回復する
+    Heal {
+        amount: u32,
+    },
+    // MPを回復する
+    RestoreMp {
+        amount: u32,
+    },
+    // バトルから確実に逃げる（バトル中のみ）
+    Escape,
+    // 攻撃力を数ターン上げる（バトル中のみ）
+    AttackBuff {
+        amount: u32,
+        turns: u32,
+    },
+    // 状態異常を治す（status を省略するとすべて治す）
+    Cure {
+        #[serde(default)]
+        status: Option<StatusKind>,
+    },
+}
+
+// アイテム構造体
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub effect: ItemEffect,
+    // 値段（ショップでの売値はこの半額。0 なら売れない）
+    #[serde(default)]
+    pub price: u32,
+}
+
+// 所持品（アイテムID → 個数）
+// セーブファイルにはそのままマップとして保存される
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Inventory {
+    items: BTreeMap<String, u32>,
+}
+
+impl Inventory {
+    pub fn add(&mut self, id: &str, count: u32) {
+        *self.items.entry(id.to_string()).or_insert(0) += count;
+    }
+
+    // 1個減らす。持っていなければ false
+    pub fn remove(&mut self, id: &str) -> bool {
+        match self.items.get_mut(id) {
+            Some(count) if *count > 1 => {
+                *count -= 1;
+                true
+            }
+            Some(_) => {
+                self.items.remove(id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn count(&self, id: &str) -> u32 {
+        self.items.get(id).copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // (アイテムID, 個数) を ID 順に返す
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.items.iter().map(|(id, count)| (id.as_str(), *count))
+    }
+}
+
+pub fn load_items() -> Result<Vec<Item>> {
+    load_list(ITEMS_PATH, check_items)
+}
+
+// アイテムIDの重複を確認する
+pub(crate) fn check_items(data: &str, items: &[Item]) -> Vec<GameError> {
+    let path = Path::new(ITEMS_PATH);
+    let mut problems = Vec::new();
+
+    for (i, item) in items.iter().enumerate() {
+        if items[..i].iter().any(|other| other.id == item.id) {
+            problems.push(
+                GameError::validation(path, format!("アイテムID {} が重複しています", item.id))
+                    .at_line(entry_line(data, i)),
+            );
+        }
+    }
+
+    problems
+}
+
+// IDからアイテムを探す
+pub fn find_item<'a>(items: &'a [Item], id: &str) -> Option<&'a Item> {
+    items.iter().find(|item| item.id == id)
+}
+
+// HPを回復し、実際に回復した量を返す
+pub(crate) fn heal(member: &mut PartyMember, amount: u32) -> u32 {
+    let before = member.hp;
+    member.hp = (member.hp + amount).min(member.max_hp);
+    member.hp - before
+}
+
+// MPを回復し、実際に回復した量を返す
+pub(crate) fn restore_mp(member: &mut PartyMember, amount: u32) -> u32 {
+    let before = member.mp;
+    member.mp = (member.mp + amount).min(member.max_mp);
+    member.mp - before
+}
+
+// バトル外でアイテムを使う（HP・MPの回復と状態異常を治すアイテムのみ使える）
+// 所持品は主人公のものを使い、効果はパーティの member 番目のメンバーが受ける
+pub fn use_item_in_field(
+    character: &mut Character,
+    member: usize,
+    item: &Item,
+) -> Vec<BattleEvent> {
+    let not_usable = || {
+        vec![BattleEvent::ItemNotUsable {
+            name: item.name.clone(),
+        }]
+    };
+
+    if !matches!(
+        item.effect,
+        ItemEffect::Heal { .. } | ItemEffect::RestoreMp { .. } | ItemEffect::Cure { .. }
+    ) {
+        return not_usable();
+    }
+    if !character.inventory.remove(&item.id) {
+        return vec![BattleEvent::NoItem];
+    }
+
+    let target = character.member_mut(member);
+    let mut events = vec![BattleEvent::ItemUsed {
+        actor: target.name.clone(),
+        name: item.name.clone(),
+    }];
+    match item.effect {
+        ItemEffect::Heal { amount } => events.push(BattleEvent::Heal {
+            actor: target.name.clone(),
+            amount: heal(target, amount),
+        }),
+        ItemEffect::RestoreMp { amount } => events.push(BattleEvent::MpRestored {
+            actor: target.name.clone(),
+            amount: restore_mp(target, amount),
+        }),
+        ItemEffect::Cure { status } => {
+            for status in cure(&mut target.status, status) {
+                events.push(BattleEvent::StatusRecovered {
+                    target: target.name.clone(),
+                    status,
+                });
+            }
+        }
+        ItemEffect::Escape | ItemEffect::AttackBuff { .. } => unreachable!(),
+    }
+    events
+}
@@ -0,0 +1,31 @@
+// kt_rpg のゲームエンジン
+// 画面表示や入力、待ち時間を含まない純粋なゲームルールだけを提供する
+// 表示はフロントエンド（src/main.rs など）がイベントを受け取って行う
+
+pub mod ability;
+pub mod battle;
+pub mod character;
+pub mod config;
+pub mod content;
+pub mod defeat;
+pub mod element;
+pub mod equipment;
+pub mod error;
+pub mod item;
+pub mod log;
+pub mod monster;
+pub mod party;
+pub mod save;
+pub mod shop;
+pub mod skill;
+pub mod stage;
+pub mod status;
+pub mod timing;
+pub mod validate;
+
+use rand_chacha::ChaCha8Rng;
+
+// ゲーム全体で共有する乱数生成器
+// シード値から再現できるよう、すべての乱数はこのRNGから引く
+// StdRng は rand のバージョンで中身が変わるので、アルゴリズムを名前で固定する
+pub type GameRng = ChaCha8Rng;
@@ -0,0 +1,50 @@
+use std::io::{self, Write};
+
+use crate::battle::BattleEvent;
+
+// バトルログ
+// エンジンが返したイベントを順に貯め、絞り込みや JSON Lines 出力に使う
+#[derive(Debug, Default)]
+pub struct BattleLog {
+    events: Vec<BattleEvent>,
+}
+
+impl BattleLog {
+    pub fn new() -> Self {
+        BattleLog::default()
+    }
+
+    pub fn push(&mut self, event: BattleEvent) {
+        self.events.push(event);
+    }
+
+    pub fn extend(&mut self, events: impl IntoIterator<Item = BattleEvent>) {
+        self.events.extend(events);
+    }
+
+    pub fn events(&self) -> &[BattleEvent] {
+        &self.events
+    }
+
+    // 条件に合うイベントだけを返す
+    pub fn filter<'a>(
+        &'a self,
+        predicate: impl Fn(&BattleEvent) -> bool + 'a,
+    ) -> impl Iterator<Item = &'a BattleEvent> + 'a {
+        self.events.iter().filter(move |e| predicate(e))
+    }
+
+    // 指定したイベント名（"attack" など）のイベントだけを返す
+    pub fn of_kind<'a>(&'a self, kind: &'a str) -> impl Iterator<Item = &'a BattleEvent> + 'a {
+        self.filter(move |e| e.kind() == kind)
+    }
+
+    // 1行に1イベントの JSON Lines 形式で書き出す
+    pub fn write_json_lines(&self, writer: &mut impl Write) -> io::Result<()> {
+        for event in &self.events {
+            serde_json::to_writer(&mut *writer, event)?;
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
@@ -1,408 +1,1245 @@
 use std::{
     fs::File,
-    io::{self, stdin, Read, Write},
-    path::Path,
-    thread,
-    time::Duration,
+    io::{self, stdin, BufWriter, Write},
+    process,
+    time::{Duration, SystemTime},
 };
 
 use colored::Colorize;
-use rand::{seq::SliceRandom, thread_rng, Rng};
-use serde::{Deserialize, Serialize};
+use kt_rpg::{
+    battle::{Action, Battle, BattleEvent, BattleResult},
+    character::{Character, PartyMember},
+    config::{load_config, CONFIG_PATH},
+    content::Content,
+    defeat::{apply_defeat, DefeatRules, Hardcore, Respawn},
+    equipment::{equip, equipment_bonus, find_equipment, unequip, EquipSlot, Equipment},
+    item::{find_item, use_item_in_field, Item},
+    log::BattleLog,
+    monster::{
+        apply_encounter_weights, choose_monsters, choose_stage_monsters, get_boss_monster, Monster,
+    },
+    party::recruit,
+    save::{
+        copy_slot, corrupt_path, delete_slot, discard_save, list_slots, load_character,
+        migrate_legacy_save, migrate_save, migration_backup_path, rename_slot, restore_backup,
+        save_character, validate_slot_name, SlotSummary, LEGACY_SAVE_PATH, SAVE_VERSION,
+    },
+    shop::{buy, restock, sell, sell_price, ShopEntry},
+    skill::{find_skill, learn_skills, Skill},
+    stage::{find_stage, is_stage_unlocked, EncounterRule, Stage},
+    status::StatusEffect,
+    timing::{GameSpeed, Pacer, SpeedPacer},
+    validate::validate_content,
+    GameRng,
+};
+use rand::SeedableRng;
+
+// コマンドライン引数
+struct Options {
+    seed: Option<u64>,                 // --seed で指定された乱数シード
+    event_log: Option<String>,         // --event-log で指定されたイベントログの出力先
+    event_filter: Option<Vec<String>>, // --event-filter で指定された出力するイベント名
+    speed: Option<GameSpeed>,          // --speed で指定された進行速度（config.yaml より優先）
+    validate: bool,                    // validate サブコマンド（データファイルを検証して終了する）
+}
+
+// コマンドライン引数を解析する
+fn parse_args() -> Options {
+    let mut options = Options {
+        seed: None,
+        event_log: None,
+        event_filter: None,
+        speed: None,
+        validate: false,
+    };
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => match args.next().map(|v| v.parse::<u64>()) {
+                Some(Ok(seed)) => options.seed = Some(seed),
+                _ => println!("❌ --seed には数値を指定してください。"),
+            },
+            "--event-log" => match args.next() {
+                Some(path) => options.event_log = Some(path),
+                None => println!("❌ --event-log には出力先のパスを指定してください。"),
+            },
+            "--event-filter" => match args.next() {
+                Some(kinds) => {
+                    options.event_filter = Some(kinds.split(',').map(str::to_string).collect())
+                }
+                None => {
+                    println!("❌ --event-filter にはイベント名をカンマ区切りで指定してください。")
+                }
+            },
+            "--speed" => match args.next().as_deref().and_then(GameSpeed::from_name) {
+                Some(speed) => options.speed = Some(speed),
+                None => println!("❌ --speed には normal / fast / instant を指定してください。"),
+            },
+            "validate" => options.validate = true,
+            _ => println!("❌ 不明な引数です: {}", arg),
+        }
+    }
+
+    options
+}
+
+// プレイ中に持ち回る状態
+struct Session {
+    slot: String, // プレイ中のセーブスロット
+    content: Content,
+    encounter: EncounterRule, // 通常モード・ブーストモードの群れの組み方（config.yaml）
+    rng: GameRng,
+    log: BattleLog,
+    pacer: Box<dyn Pacer>,
+}
+
+impl Session {
+    // イベントを表示し、ログに記録する
+    fn emit(&mut self, character: &Character, event: BattleEvent) {
+        render_event(character, &self.content, &event, self.pacer.as_mut());
+        self.log.push(event);
+    }
+
+    // セーブする。失敗してもゲームは止めずにエラーを表示する
+    fn save(&self, character: &Character) {
+        if let Err(e) = save_character(&self.slot, character) {
+            println!("❌ セーブに失敗しました: {}", e);
+        }
+    }
+
+    // 演出のために待つ（秒数は通常速度での値）
+    fn pause(&mut self, secs: u64) {
+        self.pacer.pause(Duration::from_secs(secs));
+    }
+}
 
 // ゲームモード
 enum Mode {
-    Normal, // 通常モード（既存）
-    Boost,  // ブーストモード（既存）
-    Stage,  // ステージモード（新規）
-}
-
-// ステージを表すenum
-// 各ステージには解放に必要なレベル、バトル数、テーマがある
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
-enum Stage {
-    Grassland = 1, // 草原 (Lv1解放) - 弱い魔物が住む平和な草原
-    Forest = 2,    // 森 (Lv8解放) - 野生の獣や妖精が住む森
-    Cave = 3,      // 洞窟 (Lv15解放) - アンデッドや闇の生物が住む
-    Mountain = 4,  // 山 (Lv25解放) - 強力な魔物が生息する山岳地帯
-    Castle = 5,    // 城 (Lv40解放) - 魔王の城、最強の敵が待ち受ける
-}
-
-impl Stage {
-    // 各ステージの解放に必要なレベルを返す
-    fn required_level(&self) -> u32 {
-        match self {
-            Stage::Grassland => 1,
-            Stage::Forest => 8,
-            Stage::Cave => 15,
-            Stage::Mountain => 25,
-            Stage::Castle => 40,
-        }
-    }
-
-    // ステージの日本語名を返す
-    fn name(&self) -> &'static str {
-        match self {
-            Stage::Grassland => "草原",
-            Stage::Forest => "森",
-            Stage::Cave => "洞窟",
-            Stage::Mountain => "山",
-            Stage::Castle => "城",
-        }
-    }
-
-    // ステージのバトル数を返す（ボス戦を除く）
-    fn battle_count(&self) -> usize {
-        match self {
-            Stage::Grassland => 5,
-            Stage::Forest => 7,
-            Stage::Cave => 9,
-            Stage::Mountain => 11,
-            Stage::Castle => 13,
-        }
-    }
-
-    // 全ステージを配列で返す
-    fn all() -> [Stage; 5] {
-        [
-            Stage::Grassland,
-            Stage::Forest,
-            Stage::Cave,
-            Stage::Mountain,
-            Stage::Castle,
-        ]
-    }
-
-    // ステージ番号からStageを取得
-    fn from_number(n: u32) -> Option<Stage> {
-        match n {
-            1 => Some(Stage::Grassland),
-            2 => Some(Stage::Forest),
-            3 => Some(Stage::Cave),
-            4 => Some(Stage::Mountain),
-            5 => Some(Stage::Castle),
-            _ => None,
-        }
-    }
-}
-
-// デフォルトのステージ値（互換性のため）
-fn default_stage() -> u32 {
-    1
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-struct Stats {
-    min_attack: u32,
-    max_attack: u32,
-    min_recovery: u32,
-    max_recovery: u32,
-}
-
-// キャラクター構造体
-// ステージシステム対応のため、stages_clearedとcurrent_stageを追加
-#[derive(Serialize, Deserialize, Debug)]
-struct Character {
-    name: String,
-    lv: u32,
-    hp: u32,
-    max_hp: u32,
-    stats: Stats,
-    exp: u32,
-    // クリア済みステージ番号のリスト（互換性のためデフォルト値を設定）
-    #[serde(default)]
-    stages_cleared: Vec<u32>,
-    // 現在挑戦中のステージ（未選択時はNone）
-    #[serde(default)]
-    current_stage: Option<u32>,
-}
-
-// モンスター構造体
-// ステージシステム対応のため、stageとis_bossを追加
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Monster {
-    name: String,
-    hp: u32,
-    max_hp: u32,
-    min_attack: u32,
-    max_attack: u32,
-    exp: u32,
-    // 所属ステージ番号 (1-5)、互換性のためデフォルト値を設定
-    #[serde(default = "default_stage")]
-    stage: u32,
-    // ボスモンスターフラグ
-    #[serde(default)]
-    is_boss: bool,
-}
-
-fn load_or_create_character() -> io::Result<Character> {
-    let path = Path::new("savefile.yaml");
-
-    if path.exists() {
-        let mut file = File::open(path)?;
-        let mut data = String::new();
-        file.read_to_string(&mut data)?;
-        let character: Character = serde_yaml::from_str(&data).unwrap();
-        Ok(character)
-    } else {
-        println!("新しいキャラクターを作成します。名前を入力してください:");
-        let mut name = String::new();
-        io::stdin().read_line(&mut name)?;
-        let name = name.trim().to_string();
+    Normal,    // 通常モード（既存）
+    Boost,     // ブーストモード（既存）
+    Stage,     // ステージモード（新規）
+    Shop,      // 町のショップ（終わるとモード選択に戻る）
+    Equipment, // 装備メニュー（終わるとモード選択に戻る）
+}
 
-        let character = Character {
-            name,
-            lv: 1,
-            hp: 50,
-            max_hp: 50,
-            stats: Stats {
-                min_attack: 2,
-                max_attack: 5,
-                min_recovery: 1,
-                max_recovery: 3,
-            },
-            exp: 0,
-            stages_cleared: Vec::new(),
-            current_stage: None,
-        };
+// セーブの読み込みに失敗したときの復旧方法
+enum Recovery {
+    NewCharacter, // 新しいキャラクターを作る
+    Quit,         // 終了する
+}
+
+fn select_recovery(slot: &str) -> Result<Recovery, InputClosed> {
+    println!("どうしますか？");
+    println!(
+        "1. 新しいキャラクターを作る（壊れたセーブは {} に退避します）",
+        corrupt_path(slot).display()
+    );
+    println!("2. 終了する");
+
+    Ok(match read_input()?.as_str() {
+        "1" => Recovery::NewCharacter,
+        _ => Recovery::Quit,
+    })
+}
 
-        let data = serde_yaml::to_string(&character).unwrap();
-        let mut file = File::create(path)?;
-        file.write_all(data.as_bytes())?;
-        Ok(character)
+// 入力が閉じられた（EOF）
+// main まで戻り、イベントログを書き出してから終了する
+struct InputClosed;
+
+// 1行読み込み、前後の空白を除いて返す
+// UTF-8 として読めない行は捨てて入力し直してもらい、それ以外の読み込みエラーは EOF と同じに扱う
+fn read_input() -> Result<String, InputClosed> {
+    loop {
+        let mut input = String::new();
+        match stdin().read_line(&mut input) {
+            Ok(0) => return Err(InputClosed),
+            Ok(_) => return Ok(input.trim().to_string()),
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                println!("❌ 入力を読み取れませんでした。もう一度入力してください:");
+            }
+            Err(_) => return Err(InputClosed),
+        }
     }
 }
 
-fn battle(character: &mut Character, monster: &Monster) -> bool {
-    let mut rng = rand::thread_rng();
-    let mut monster_hp = monster.hp;
+// 新しいキャラクターを作成し、同名のスロットにセーブする
+// 名前が使えない場合はNone
+fn create_character() -> Result<Option<(String, Character)>, InputClosed> {
+    println!("新しいキャラクターを作成します。名前を入力してください:");
+    let name = read_input()?;
 
-    println!("🦕{}が現れた！", monster.name);
+    if let Err(e) = validate_slot_name(&name) {
+        println!("❌ {}", e);
+        return Ok(None);
+    }
 
-    while character.hp > 0 && monster_hp > 0 {
-        println!(
-            "{} HP: {}/{} | {} HP: {}/{}",
-            character.name.green(),
-            character.hp.to_string().green(),
-            character.max_hp.to_string().green(),
-            monster.name.red(),
-            monster_hp.to_string().red(),
-            monster.max_hp.to_string().red()
-        );
+    let mut character = Character::new(name.clone());
+    character.defeat_rules = select_defeat_rules()?;
+    if let Err(e) = save_character(&name, &character) {
+        println!("❌ セーブに失敗しました: {}", e);
+    }
+    Ok(Some((name, character)))
+}
 
-        thread::sleep(Duration::from_secs(1));
+// 全滅したときのルールを選ばせる（無効な入力ならペナルティ無しで町に戻される）
+fn select_defeat_rules() -> Result<DefeatRules, InputClosed> {
+    let presets = DefeatRules::presets();
 
-        // 🎲 decide attack or recovery for random
-        if rng.gen_bool(0.5) {
-            let attack = rng.gen_range(character.stats.min_attack..=character.stats.max_attack);
-            println!("⚔️ {}の攻撃！ {}のダメージ", character.name, attack);
-            monster_hp = monster_hp.saturating_sub(attack);
-        } else {
-            let recovery =
-                rng.gen_range(character.stats.min_recovery..=character.stats.max_recovery);
-            character.hp = (character.hp + recovery).min(character.max_hp);
-            println!("❤️ {}は回復した！ {}のHPを回復", character.name, recovery);
+    println!("全滅したときのルールを選択してください（後から変えることはできません）:");
+    for (i, (name, description, _)) in presets.iter().enumerate() {
+        println!("{}. {} - {}", i + 1, name, description);
+    }
+
+    Ok(match read_input()?.parse::<usize>() {
+        Ok(n) if (1..=presets.len()).contains(&n) => presets[n - 1].2,
+        _ => {
+            println!("無効な入力です。ペナルティ無しで町に戻されるルールにします。");
+            DefeatRules::default()
         }
+    })
+}
 
-        thread::sleep(Duration::from_secs(3));
+// スロットのセーブを読み込む
+// 壊れていれば読み込める最新のバックアップから復元し、それも無ければ復旧方法を選ばせる
+// 終了を選んだ場合はNone
+fn load_slot(slot: &str) -> Result<Option<(String, Character)>, InputClosed> {
+    // 古い形式のセーブは元のファイルを残してから新しい形式に変換する
+    match migrate_save(slot) {
+        Ok(Some(version)) => println!(
+            "📦 セーブデータをバージョン{}からバージョン{}に更新しました。（元のファイル: {}）",
+            version,
+            SAVE_VERSION,
+            migration_backup_path(slot, version).display()
+        ),
+        Ok(None) => {}
+        Err(e) => println!("❌ セーブデータの更新に失敗しました: {}", e),
+    }
 
-        if monster_hp == 0 {
-            println!("{}", format!("{}を倒した！", monster.name).yellow());
-            println!("{}", format!("{}の経験値を得た！💪", monster.exp).blue());
+    loop {
+        match load_character(slot) {
+            Ok(character) => return Ok(Some((slot.to_string(), character))),
+            Err(e) => {
+                println!("{}", "❌ セーブデータを読み込めませんでした。".red());
+                println!("{}", e);
+
+                match restore_backup(slot) {
+                    Ok((backup, character)) => {
+                        println!(
+                            "✅ バックアップ {} から復元しました。（壊れたセーブは {} に退避しました）",
+                            backup.display(),
+                            corrupt_path(slot).display()
+                        );
+                        return Ok(Some((slot.to_string(), character)));
+                    }
+                    Err(e) => println!("❌ バックアップから復元できませんでした: {}", e),
+                }
 
-            character.exp += monster.exp;
-            check_level_up(character);
+                match select_recovery(slot)? {
+                    Recovery::NewCharacter => {
+                        if let Err(e) = discard_save(slot) {
+                            println!("❌ {}", e);
+                            continue;
+                        }
+                        let character = Character::new(slot.to_string());
+                        if let Err(e) = save_character(slot, &character) {
+                            println!("❌ セーブに失敗しました: {}", e);
+                        }
+                        return Ok(Some((slot.to_string(), character)));
+                    }
+                    Recovery::Quit => return Ok(None),
+                }
+            }
+        }
+    }
+}
 
-            character.hp = character.max_hp;
-            save_character(character).expect("セーブ中にエラーが発生しました。");
+// 最後にプレイしてからの経過時間を「3日前」のような文字列にする
+fn format_elapsed(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    match secs {
+        0..=59 => "たった今".to_string(),
+        60..=3599 => format!("{}分前", secs / 60),
+        3600..=86399 => format!("{}時間前", secs / 3600),
+        _ => format!("{}日前", secs / 86400),
+    }
+}
 
-            return true;
+// 操作対象のスロットを番号で選ばせる
+fn pick_slot<'a>(slots: &'a [SlotSummary], verb: &str) -> Result<Option<&'a str>, InputClosed> {
+    println!("{}するスロットの番号を入力してください:", verb);
+    Ok(match read_input()?.parse::<usize>() {
+        Ok(n) if (1..=slots.len()).contains(&n) => Some(&slots[n - 1].slot),
+        _ => {
+            println!("❌ 無効な入力です。");
+            None
         }
+    })
+}
+
+// セーブスロット選択画面を表示し、開始するスロットとキャラクターを返す
+// 終了を選んだ場合はNone
+fn select_slot(stages: &[Stage]) -> Result<Option<(String, Character)>, InputClosed> {
+    loop {
+        let slots = list_slots().unwrap_or_else(|e| {
+            println!("❌ {}", e);
+            Vec::new()
+        });
 
-        let attack = rng.gen_range(monster.min_attack..=monster.max_attack);
-        println!("👊{}の攻撃！ {}のダメージ", monster.name, attack);
-        character.hp = character.hp.saturating_sub(attack);
+        println!("\n📂 セーブスロットを選択してください:");
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
-        thread::sleep(Duration::from_secs(3));
+        if slots.is_empty() {
+            println!("（セーブデータがありません）");
+        }
 
-        if character.hp == 0 {
-            println!("{}", format!("{}は倒れた...🚑", character.name).red());
-            return false;
+        for (i, summary) in slots.iter().enumerate() {
+            let played = summary.last_played.map(format_elapsed).unwrap_or_default();
+
+            match &summary.character {
+                Ok(character) if character.game_over => println!(
+                    "{}. {} Lv{} | 💀 ゲームオーバー | {}",
+                    i + 1,
+                    summary.slot.bright_black(),
+                    character.hero.lv,
+                    played
+                ),
+                Ok(character) => println!(
+                    "{}. {} Lv{} | クリア {}/{} | {}",
+                    i + 1,
+                    summary.slot.green(),
+                    character.hero.lv,
+                    character.stages_cleared.len(),
+                    stages.len(),
+                    played
+                ),
+                Err(_) => println!(
+                    "{}. {} ⚠️ 読み込めません | {}",
+                    i + 1,
+                    summary.slot.red(),
+                    played
+                ),
+            }
         }
 
-        thread::sleep(Duration::from_secs(3));
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        println!("n. 新規作成 | r. 名前変更 | c. コピー | d. 削除 | 0. 終了");
+
+        let input = read_input()?;
+        match input.as_str() {
+            "0" => return Ok(None),
+            "n" => {
+                if let Some(created) = create_character()? {
+                    return Ok(Some(created));
+                }
+            }
+            "r" => {
+                if let Some(slot) = pick_slot(&slots, "名前を変更")? {
+                    println!("新しい名前を入力してください:");
+                    if let Err(e) = rename_slot(slot, &read_input()?) {
+                        println!("❌ {}", e);
+                    }
+                }
+            }
+            "c" => {
+                if let Some(slot) = pick_slot(&slots, "コピー")? {
+                    println!("コピー先の名前を入力してください:");
+                    if let Err(e) = copy_slot(slot, &read_input()?) {
+                        println!("❌ {}", e);
+                    }
+                }
+            }
+            "d" => {
+                if let Some(slot) = pick_slot(&slots, "削除")? {
+                    println!("本当に {} を削除しますか？ (y/n)", slot);
+                    if read_input()?.to_lowercase() == "y" {
+                        if let Err(e) = delete_slot(slot) {
+                            println!("❌ {}", e);
+                        }
+                    }
+                }
+            }
+            _ => match input.parse::<usize>() {
+                Ok(n) if (1..=slots.len()).contains(&n) => {
+                    if matches!(&slots[n - 1].character, Ok(c) if c.game_over) {
+                        println!("❌ このキャラクターはハードコアで全滅したため、もう遊べません。");
+                        continue;
+                    }
+                    return load_slot(&slots[n - 1].slot);
+                }
+                _ => println!("❌ 無効な入力です。"),
+            },
+        }
     }
+}
 
-    false // false for default
+// 行動メニューを表示し、入力された行動を返す
+// character はパーティのリーダー（所持品を共有する）、member は行動するメンバーの番号
+fn select_action(
+    character: &Character,
+    member: usize,
+    battle: &Battle,
+    content: &Content,
+) -> Result<Action, InputClosed> {
+    loop {
+        println!("{}の行動を選択してください:", character.member(member).name);
+        let flee = if battle.can_flee() {
+            format!("逃げる({:.0}%)", battle.flee_chance(character) * 100.0)
+        } else {
+            "逃げる(不可)".to_string()
+        };
+        println!(
+            "1. 攻撃 2. 回復 3. 防御 4. {} 5. アイテム 6. オート 7. スキル",
+            flee
+        );
+
+        match read_input()?.as_str() {
+            "1" => {
+                if let Some(target) = select_target(battle)? {
+                    return Ok(Action::Attack(target));
+                }
+            }
+            "2" => return Ok(Action::Recover),
+            "3" => return Ok(Action::Defend),
+            "4" => return Ok(Action::Flee),
+            "5" => {
+                if let Some(item) = select_item(character, &content.items)? {
+                    return Ok(Action::UseItem(item));
+                }
+            }
+            "6" => return Ok(Action::Auto),
+            "7" => {
+                if let Some(skill) = select_skill(character.member(member), &content.skills)? {
+                    // 自分にかかるスキルは対象を選ばない
+                    let target = if skill.effect.targets_enemy() {
+                        select_target(battle)?
+                    } else {
+                        Some(0)
+                    };
+                    if let Some(target) = target {
+                        return Ok(Action::Skill(skill, target));
+                    }
+                }
+            }
+            _ => println!("❌ 無効な入力です。"),
+        }
+    }
 }
 
-fn save_character(character: &Character) -> io::Result<()> {
-    let path = Path::new("savefile.yaml");
-    let data = serde_yaml::to_string(character).unwrap();
-    let mut file = File::create(path)?;
-    file.write_all(data.as_bytes())?;
-    Ok(())
+// 狙うモンスターを選ばせる（1体だけなら選ばずにそのモンスター、やめた場合はNone）
+fn select_target(battle: &Battle) -> Result<Option<usize>, InputClosed> {
+    let living = battle.living_enemies();
+    if living.len() == 1 {
+        return Ok(Some(living[0]));
+    }
+
+    println!("🎯 どのモンスターを狙いますか？");
+    for (n, &j) in living.iter().enumerate() {
+        let enemy = &battle.enemies()[j];
+        println!(
+            "{}. {} HP: {}/{}",
+            n + 1,
+            enemy.monster().name,
+            enemy.hp(),
+            enemy.monster().max_hp
+        );
+    }
+    println!("0. やめる");
+
+    Ok(match read_input()?.parse::<usize>() {
+        Ok(n) if (1..=living.len()).contains(&n) => Some(living[n - 1]),
+        _ => None,
+    })
 }
 
-// 次のレベルに必要な経験値を計算する（ドラクエII風）
-// 計算式: floor(10 * lv^1.5 + 10 * lv)
-// 例: Lv1→2: 20, Lv2→3: 48, Lv10→11: 416, Lv40→41: 2930
-fn required_exp_to_level_up(current_lv: u32) -> u32 {
-    let lv = current_lv as f64;
-    (10.0 * lv.powf(1.5) + 10.0 * lv).floor() as u32
+// 所持品を表示し、使うアイテムを選ばせる（やめた場合はNone）
+fn select_item(character: &Character, items: &[Item]) -> Result<Option<Item>, InputClosed> {
+    let owned: Vec<(&Item, u32)> = character
+        .inventory
+        .iter()
+        .filter_map(|(id, count)| find_item(items, id).map(|item| (item, count)))
+        .collect();
+
+    if owned.is_empty() {
+        println!("使えるアイテムを持っていません。");
+        return Ok(None);
+    }
+
+    println!("🎒 使うアイテムを選択してください:");
+    for (i, (item, count)) in owned.iter().enumerate() {
+        println!("{}. {} x{} - {}", i + 1, item.name, count, item.description);
+    }
+    println!("0. やめる");
+
+    Ok(match read_input()?.parse::<usize>() {
+        Ok(n) if (1..=owned.len()).contains(&n) => Some(owned[n - 1].0.clone()),
+        _ => None,
+    })
+}
+
+// アイテムを使う相手や装備を変えるメンバーを選ばせる（1人だけなら選ばずに主人公、やめた場合はNone）
+fn select_member(character: &Character, prompt: &str) -> Result<Option<usize>, InputClosed> {
+    if character.party_size() == 1 {
+        return Ok(Some(0));
+    }
+
+    println!("{}", prompt);
+    for i in 0..character.party_size() {
+        let member = character.member(i);
+        println!(
+            "{}. {} HP: {}/{}",
+            i + 1,
+            member.name,
+            member.hp,
+            member.max_hp
+        );
+    }
+    println!("0. やめる");
+
+    Ok(match read_input()?.parse::<usize>() {
+        Ok(n) if (1..=character.party_size()).contains(&n) => Some(n - 1),
+        _ => None,
+    })
 }
 
-#[allow(dead_code)]
-fn check_level_up(character: &mut Character) {
-    while character.exp >= required_exp_to_level_up(character.lv) {
-        character.exp -= required_exp_to_level_up(character.lv);
-        character.lv += 1;
-        println!("😊レベルアップ！ {}レベルになりました！", character.lv);
+// パーティ全員のHP・MPと状態異常を1行ずつ表示する
+fn print_party(character: &Character) {
+    for i in 0..character.party_size() {
+        let member = character.member(i);
+        println!(
+            "{} Lv{} HP: {}/{} MP: {}/{}{}",
+            member.name.green(),
+            member.lv,
+            member.hp.to_string().green(),
+            member.max_hp.to_string().green(),
+            member.mp,
+            member.max_mp,
+            format_status(&member.status).magenta()
+        );
+    }
+}
 
-        let mut rng = rand::thread_rng();
-        let hp_increase = rng.gen_range(5..=10);
-        character.max_hp += hp_increase;
-        character.hp = character.max_hp;
-        println!("🙌HPが{}増加しました！", hp_increase);
+// HP表示の後ろにつける状態異常の一覧（無ければ空文字）
+fn format_status(effects: &[StatusEffect]) -> String {
+    effects
+        .iter()
+        .map(|effect| format!(" [{}:{}]", effect.kind.name(), effect.turns))
+        .collect()
+}
 
-        let attack_increase = rng.gen_range(1..=3);
-        character.stats.min_attack += attack_increase;
-        character.stats.max_attack += attack_increase;
-        println!("⚔️攻撃力が{}増加しました！", attack_increase);
+// 覚えたスキルを表示し、使うスキルを選ばせる（やめた場合はNone）
+fn select_skill(member: &PartyMember, skills: &[Skill]) -> Result<Option<Skill>, InputClosed> {
+    let learned: Vec<&Skill> = member
+        .skills
+        .iter()
+        .filter_map(|id| find_skill(skills, id))
+        .collect();
 
-        let recovery_increase = rng.gen_range(1..=3);
+    if learned.is_empty() {
+        println!("まだスキルを覚えていません。");
+        return Ok(None);
+    }
 
-        character.stats.min_recovery += recovery_increase;
-        character.stats.max_recovery += recovery_increase;
-        println!("🛡️回復力が{}増加しました！", recovery_increase);
+    println!(
+        "✨ 使うスキルを選択してください (MP: {}/{}):",
+        member.mp, member.max_mp
+    );
+    for (i, skill) in learned.iter().enumerate() {
+        println!(
+            "{}. {} (MP {}) - {}",
+            i + 1,
+            skill.name,
+            skill.mp_cost,
+            skill.description
+        );
     }
+    println!("0. やめる");
+
+    Ok(match read_input()?.parse::<usize>() {
+        Ok(n) if (1..=learned.len()).contains(&n) => Some(learned[n - 1].clone()),
+        _ => None,
+    })
 }
 
-fn load_monsters() -> io::Result<Vec<Monster>> {
-    let path = Path::new("monsters.yaml");
+// バトルの合間にアイテムを使うか、先へ進むかを選ばせる
+fn field_menu(character: &mut Character, session: &mut Session) -> Result<(), InputClosed> {
+    loop {
+        print_party(character);
+        println!("Enter: 先へ進む | i: アイテムを使う | r: 休む（HP・MP回復、状態異常を治す）");
+
+        match read_input()?.as_str() {
+            "i" => {}
+            "r" => {
+                for (target, status) in character.rest() {
+                    session.emit(character, BattleEvent::StatusRecovered { target, status });
+                }
+                println!("🏕️ {}たちはひと休みした。", character.hero.name);
+                session.save(character);
+                continue;
+            }
+            _ => return Ok(()),
+        }
 
-    println!("モンスターのデータを読み込み中...");
+        if let Some(item) = select_item(character, &session.content.items)? {
+            let Some(member) = select_member(character, "誰に使いますか？")? else {
+                continue;
+            };
+            for event in use_item_in_field(character, member, &item) {
+                session.emit(character, event);
+            }
+            session.save(character);
+        }
+    }
+}
 
-    if path.exists() {
-        let mut file = File::open(path)?;
-        let mut data = String::new();
-        file.read_to_string(&mut data)?;
-        let monsters: Vec<Monster> = serde_yaml::from_str(&data).unwrap();
-        Ok(monsters)
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::NotFound,
-            "モンスターデータが見つかりません。",
-        ))
+// イベントを表示する
+fn render_event(
+    character: &Character,
+    content: &Content,
+    event: &BattleEvent,
+    pacer: &mut dyn Pacer,
+) {
+    match event {
+        BattleEvent::StageStarted {
+            stage,
+            name,
+            battles,
+        } => {
+            println!("\n🏰 ステージ{}: {} に挑戦！", stage, name);
+            println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+            println!("バトル数: {} + ボス戦", battles);
+            println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+        }
+        BattleEvent::StageCleared { stage, name } => {
+            println!("\n🎊 ステージ{}: {} クリア！", stage, name);
+            println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        }
+        BattleEvent::StageFailed { name, boss, .. } => match boss {
+            Some(boss) => println!("\n💀 ボス {} に敗北...", boss),
+            None => println!("\n💀 ステージ{}で敗北...", name),
+        },
+        BattleEvent::Encounter { name, .. } => println!("🦕{}が現れた！", name),
+        BattleEvent::Attack {
+            actor,
+            target,
+            damage,
+        } => {
+            println!("⚔️ {}の攻撃！ {}に{}のダメージ", actor, target, damage);
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::Heal { actor, amount } => {
+            println!("❤️ {}は回復した！ {}のHPを回復", actor, amount);
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::Defend { actor } => {
+            println!("🛡️ {}は身を守っている！", actor);
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::FleeSucceeded => {
+            println!("💨 {}たちはうまく逃げ切った！", character.hero.name);
+        }
+        BattleEvent::FleeFailed => {
+            println!("💦 {}たちは逃げられなかった！", character.hero.name);
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::CannotFlee => println!("❌ ボスからは逃げられない！"),
+        BattleEvent::NoItem => println!("使えるアイテムを持っていません。"),
+        BattleEvent::ItemUsed { actor, name } => println!("🎒 {}は{}を使った！", actor, name),
+        BattleEvent::ItemNotUsable { name } => println!("❌ {}は今は使えません。", name),
+        BattleEvent::MpRestored { actor, amount } => {
+            println!("🔷 {}のMPが{}回復した！", actor, amount);
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::AttackBuffed {
+            target,
+            amount,
+            turns,
+        } => {
+            println!(
+                "💪 {}ターンの間、{}の攻撃力が{}上がった！",
+                turns, target, amount
+            );
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::DefenseBuffed {
+            target,
+            amount,
+            turns,
+        } => {
+            println!(
+                "🛡️ {}ターンの間、{}の防御力が{}上がった！",
+                turns, target, amount
+            );
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::RegenStarted {
+            target,
+            amount,
+            turns,
+        } => {
+            println!(
+                "🌿 {}ターンの間、{}のHPが毎ターン{}回復する！",
+                turns, target, amount
+            );
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::Regenerated { target, amount } => {
+            println!("🌿 {}のHPが{}回復した。", target, amount);
+        }
+        BattleEvent::BuffExpired { target, buff } => {
+            println!("💨 {}の{}の効果が切れた。", target, buff.name())
+        }
+        BattleEvent::SkillUsed {
+            actor,
+            name,
+            mp_cost,
+        } => {
+            println!("✨ {}は{}を使った！ (MP -{})", actor, name, mp_cost);
+        }
+        BattleEvent::NotEnoughMp { name } => println!("❌ MPが足りないため{}を使えません。", name),
+        BattleEvent::SpellHit {
+            target,
+            element,
+            damage,
+        } => {
+            println!(
+                "🔮 {}属性の魔法！ {}に{}のダメージ",
+                element.name(),
+                target,
+                damage
+            );
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::StatusInflicted {
+            target,
+            status,
+            turns,
+        } => {
+            println!(
+                "{}",
+                format!(
+                    "🌀 {}は{}になった！ ({}ターン)",
+                    target,
+                    status.name(),
+                    turns
+                )
+                .magenta()
+            );
+        }
+        BattleEvent::StatusMissed { target, status } => {
+            println!("{}は{}にならなかった。", target, status.name());
+        }
+        BattleEvent::StatusDamage {
+            target,
+            status,
+            damage,
+        } => {
+            println!("☠️ {}は{}で{}のダメージ！", target, status.name(), damage);
+            pacer.pause(Duration::from_secs(1));
+        }
+        BattleEvent::StatusBlocked { target, status } => {
+            println!("💤 {}は{}で動けない！", target, status.name());
+            pacer.pause(Duration::from_secs(1));
+        }
+        BattleEvent::StatusRecovered { target, status } => {
+            println!("✨ {}の{}が治った。", target, status.name());
+        }
+        BattleEvent::MonsterAbility { monster, ability } => {
+            println!("{}", format!("🔥 {}の{}！", monster, ability).red());
+            pacer.pause(Duration::from_secs(1));
+        }
+        BattleEvent::Charging { monster, ability } => {
+            println!("{}は{}の力をためている...", monster, ability);
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::MonsterHealed { monster, amount } => {
+            println!("💚 {}のHPが{}回復した！", monster, amount);
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::Enraged { monster, attack } => {
+            println!(
+                "{}",
+                format!(
+                    "💢 {}は怒り狂っている！ 攻撃力が{}上がった！",
+                    monster, attack
+                )
+                .red()
+            );
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::PhaseChanged {
+            monster,
+            name,
+            hp,
+            dialogue,
+        } => {
+            println!("{}", format!("⚡ {}の様子がおかしい...！", monster).red());
+            pacer.pause(Duration::from_secs(2));
+            for line in dialogue {
+                println!("{}", line.bold());
+                pacer.pause(Duration::from_secs(2));
+            }
+            println!(
+                "{}",
+                format!("👿 {}が現れた！ (HP: {})", name, hp).red().bold()
+            );
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::Summoned { monster, minion } => {
+            println!("📣 {}は{}を呼び寄せた！", monster, minion);
+            pacer.pause(Duration::from_secs(1));
+        }
+        BattleEvent::Effective { element } => {
+            println!(
+                "{}",
+                format!(
+                    "💥 {}属性が弱点を突いた！ 効果はばつぐんだ！",
+                    element.name()
+                )
+                .yellow()
+            );
+        }
+        BattleEvent::Resisted { element } => {
+            println!("🪨 {}属性は効きにくいようだ...", element.name());
+        }
+        BattleEvent::DamageTaken {
+            monster,
+            target,
+            damage,
+        } => {
+            println!("👊{}の攻撃！ {}に{}のダメージ", monster, target, damage);
+            pacer.pause(Duration::from_secs(3));
+        }
+        BattleEvent::MonsterDefeated { name } => {
+            println!("{}", format!("{}を倒した！", name).yellow());
+        }
+        BattleEvent::ExpGained { exp } => {
+            println!("{}", format!("{}の経験値を得た！💪", exp).blue());
+        }
+        BattleEvent::GoldGained { gold } => {
+            println!("{}", format!("💰 {}ゴールドを手に入れた！", gold).yellow());
+        }
+        BattleEvent::ItemDropped { item, count } => {
+            println!("🎁 {}を{}個手に入れた！", content.name_of(item), count);
+        }
+        BattleEvent::LevelUp(level_up) => {
+            println!(
+                "😊レベルアップ！ {}は{}レベルになりました！",
+                level_up.name, level_up.lv
+            );
+            println!("🙌HPが{}増加しました！", level_up.hp_increase);
+            println!("⚔️攻撃力が{}増加しました！", level_up.attack_increase);
+            println!("🛡️回復力が{}増加しました！", level_up.recovery_increase);
+            println!("🔷MPが{}増加しました！", level_up.mp_increase);
+            if level_up.speed_increase > 0 {
+                println!("👟素早さが{}増加しました！", level_up.speed_increase);
+            }
+            for id in &level_up.learned {
+                if let Some(skill) = find_skill(&content.skills, id) {
+                    println!("📖{}を覚えた！", skill.name);
+                }
+            }
+        }
+        BattleEvent::MemberDown { name } => {
+            println!("{}", format!("{}は倒れた...🚑", name).red());
+        }
+        BattleEvent::MemberJoined { name } => {
+            println!("{}", format!("🤝 {}が仲間に加わった！", name).green());
+        }
+        BattleEvent::PartyFull { name } => {
+            println!("パーティが満員のため、{}は仲間にできなかった。", name);
+        }
+        BattleEvent::ShopRestocked => {
+            println!("🏪 町のショップに品物が補充された。");
+        }
+        BattleEvent::Defeat => {
+            println!(
+                "{}",
+                format!("{}たちは全滅した...🚑", character.hero.name).red()
+            );
+        }
+        BattleEvent::GoldLost { gold } => {
+            println!("{}", format!("💸 {}ゴールドを失った...", gold).red());
+        }
+        BattleEvent::ExpLost { name, exp } => {
+            println!(
+                "{}",
+                format!("📉 {}は{}の経験値を失った...", name, exp).red()
+            );
+        }
+        BattleEvent::Revived { respawn } => match respawn {
+            Respawn::Town => println!("🏘️ {}たちは町で目を覚ました。", character.hero.name),
+            Respawn::Spot => println!("🌅 {}たちはなんとか起き上がった。", character.hero.name),
+        },
+        BattleEvent::GameOver { hardcore } => {
+            println!("{}", "⚰️ GAME OVER ⚰️".red().bold());
+            match hardcore {
+                Hardcore::Delete => println!("{}の冒険の記録は失われた...", character.hero.name),
+                Hardcore::Lock => println!("{}の冒険はここで終わった...", character.hero.name),
+            }
+        }
     }
 }
 
-fn choose_monsters(monsters: &[Monster]) -> Vec<Monster> {
-    let high = vec!["ゴブリン", "オオカミ", "スライム", "コウモリ", "ゾンビ"];
-    let mid = vec!["スケルトン", "オーク", "ハーピー"];
-    let low = vec!["ミノタウロス", "トロール"];
-    let rare = vec!["ドラゴン"];
+// monsters は一緒に現れるモンスターの群れ
+// auto が true の場合は入力を求めず、従来どおりランダムに攻撃か回復を選ぶ
+fn battle(
+    character: &mut Character,
+    monsters: &[Monster],
+    auto: bool,
+    session: &mut Session,
+) -> Result<BattleResult, InputClosed> {
+    let mut battle = Battle::new(monsters.to_vec());
+    let mut auto = auto;
+
+    for event in battle.encounter() {
+        session.emit(character, event);
+    }
+
+    loop {
+        print_party(character);
+        for &j in &battle.living_enemies() {
+            let enemy = &battle.enemies()[j];
+            println!(
+                "{} HP: {}/{}{}",
+                enemy.monster().name.red(),
+                enemy.hp().to_string().red(),
+                enemy.monster().max_hp.to_string().red(),
+                format_status(enemy.status()).magenta()
+            );
+        }
+
+        // 倒れていないメンバーごとに行動を選ぶ（誰かがオートを選ぶと以降は全員オート）
+        // 倒れているメンバーの分は Action::Auto で埋める（バトル側で飛ばされる）
+        let mut actions = vec![Action::Auto; character.party_size()];
+        for member in character.living_members() {
+            if auto {
+                break;
+            }
+            let action = select_action(character, member, &battle, &session.content)?;
+            if action == Action::Auto {
+                auto = true;
+            }
+            actions[member] = action;
+        }
+
+        session.pause(1);
+
+        for event in battle.turn(character, actions, &session.content, &mut session.rng) {
+            session.emit(character, event);
+        }
+
+        if let Some(result) = battle.result() {
+            if result == BattleResult::Win {
+                session.save(character);
+            }
+            return Ok(result);
+        }
 
-    let mut encounter_pool: Vec<&str> = vec![];
+        session.pause(3);
+    }
+}
 
-    for name in &high {
-        encounter_pool.extend(std::iter::repeat(name).take(5));
+// イベントログを JSON Lines 形式でファイルに書き出す
+// filter が指定されていれば、そのイベント名のものだけを出力する
+fn write_event_log(log: &BattleLog, path: &str, filter: Option<&[String]>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    match filter {
+        Some(kinds) => {
+            let mut filtered = BattleLog::new();
+            filtered.extend(log.filter(|e| kinds.iter().any(|k| k == e.kind())).cloned());
+            filtered.write_json_lines(&mut writer)?;
+        }
+        None => log.write_json_lines(&mut writer)?,
     }
-    for name in &mid {
-        encounter_pool.extend(std::iter::repeat(name).take(3));
+    writer.flush()
+}
+
+// 商品を番号で選ばせる
+fn pick_entry(entries: &[ShopEntry], verb: &str) -> Result<Option<usize>, InputClosed> {
+    println!("{}商品の番号を入力してください:", verb);
+    Ok(match read_input()?.parse::<usize>() {
+        Ok(n) if (1..=entries.len()).contains(&n) => Some(n - 1),
+        _ => {
+            println!("❌ 無効な入力です。");
+            None
+        }
+    })
+}
+
+// 売る所持品を選ばせる（値段の無いものは並べない、やめた場合はNone）
+fn pick_sellable(character: &Character, content: &Content) -> Result<Option<String>, InputClosed> {
+    let sellable: Vec<(&str, u32)> = character
+        .inventory
+        .iter()
+        .filter(|(id, _)| content.price_of(id) > 0)
+        .collect();
+
+    if sellable.is_empty() {
+        println!("売れるものを持っていません。");
+        return Ok(None);
     }
-    for name in &low {
-        encounter_pool.extend(std::iter::repeat(name).take(2));
+
+    println!("売るものの番号を入力してください:");
+    for (i, (id, count)) in sellable.iter().enumerate() {
+        println!(
+            "{}. {} x{}（売値{}G）",
+            i + 1,
+            content.name_of(id),
+            count,
+            sell_price(content.price_of(id))
+        );
     }
-    for name in &rare {
-        encounter_pool.extend(std::iter::repeat(name).take(1));
+    println!("0. やめる");
+
+    Ok(match read_input()?.parse::<usize>() {
+        Ok(n) if (1..=sellable.len()).contains(&n) => Some(sellable[n - 1].0.to_string()),
+        _ => None,
+    })
+}
+
+// 町のショップ
+// 在庫はキャラクターごとに残り、ステージをクリアすると shop.yaml の値に戻る
+fn run_shop(character: &mut Character, session: &mut Session) -> Result<(), InputClosed> {
+    let entries = &session.content.shop;
+
+    println!("\n🏪 町のショップへようこそ！");
+
+    loop {
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        println!("所持金: {}", format!("{}G", character.gold).yellow());
+        for (i, entry) in entries.iter().enumerate() {
+            let stock = match entry.stock_left(character) {
+                Some(n) => format!("在庫{}", n),
+                None => "在庫∞".to_string(),
+            };
+            println!(
+                "{}. {} {}G（売値{}G）{} | 所持 {}",
+                i + 1,
+                session.content.name_of(&entry.item),
+                entry.price,
+                sell_price(session.content.price_of(&entry.item)),
+                stock,
+                character.inventory.count(&entry.item)
+            );
+        }
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        println!("b. 買う | s. 売る | 0. 店を出る");
+
+        match read_input()?.as_str() {
+            "b" => {
+                if let Some(i) = pick_entry(entries, "買う")? {
+                    let name = session.content.name_of(&entries[i].item);
+                    match buy(character, &entries[i]) {
+                        Ok(()) => println!("🛒 {}を買った！", name),
+                        Err(e) => println!("❌ {}", e),
+                    }
+                }
+            }
+            "s" => {
+                if let Some(id) = pick_sellable(character, &session.content)? {
+                    let name = session.content.name_of(&id);
+                    let price = session.content.price_of(&id);
+                    match sell(character, entries, &id, price) {
+                        Ok(price) => println!("💰 {}を{}Gで売った！", name, price),
+                        Err(e) => println!("❌ {}", e),
+                    }
+                }
+            }
+            "0" => break,
+            _ => println!("❌ 無効な入力です。"),
+        }
     }
 
-    let mut rng = thread_rng();
-    let mut selected = vec![];
+    println!("またのお越しを！");
+    session.save(character);
+    Ok(())
+}
+
+// 装備メニュー
+fn run_equipment_menu(character: &mut Character, session: &mut Session) -> Result<(), InputClosed> {
+    let equipment = &session.content.equipment;
+    // 装備を変えるメンバー（最初は主人公）
+    let mut current = 0;
+
+    loop {
+        let member = character.member(current);
+        let bonus = equipment_bonus(member, equipment);
+        println!("\n🗡️ {}の装備", member.name);
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        for (i, slot) in EquipSlot::all().iter().enumerate() {
+            let equipped = member
+                .equipment
+                .get(*slot)
+                .map_or("なし", |id| session.content.name_of(id));
+            println!("{}. {}: {}", i + 1, slot.name(), equipped);
+        }
+        println!(
+            "攻撃力: {}～{} | 回復力: {}～{} | 防御力: {}",
+            member.stats.min_attack + bonus.attack,
+            member.stats.max_attack + bonus.attack,
+            member.stats.min_recovery + bonus.recovery,
+            member.stats.max_recovery + bonus.recovery,
+            bonus.defense
+        );
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        if character.party_size() > 1 {
+            println!("e. 装備する | u. 外す | m. メンバーを変える | 0. 戻る");
+        } else {
+            println!("e. 装備する | u. 外す | 0. 戻る");
+        }
+
+        match read_input()?.as_str() {
+            "m" if character.party_size() > 1 => {
+                if let Some(i) = select_member(character, "誰の装備を変えますか？")? {
+                    current = i;
+                }
+            }
+            "e" => {
+                let owned: Vec<&Equipment> = character
+                    .inventory
+                    .iter()
+                    .filter_map(|(id, _)| find_equipment(equipment, id))
+                    .collect();
+
+                if owned.is_empty() {
+                    println!("装備品を持っていません。");
+                    continue;
+                }
+
+                println!("装備する番号を入力してください:");
+                for (i, gear) in owned.iter().enumerate() {
+                    println!(
+                        "{}. [{}] {} - {}",
+                        i + 1,
+                        gear.slot.name(),
+                        gear.name,
+                        gear.description
+                    );
+                }
+
+                match read_input()?.parse::<usize>() {
+                    Ok(n) if (1..=owned.len()).contains(&n) => {
+                        let gear = owned[n - 1].clone();
+                        match equip(character, current, &gear) {
+                            Ok(()) => println!("✅ {}を装備した！", gear.name),
+                            Err(e) => println!("❌ {}", e),
+                        }
+                    }
+                    _ => println!("❌ 無効な入力です。"),
+                }
+            }
+            "u" => {
+                println!("外す部位の番号を入力してください:");
+                let slot = match read_input()?.parse::<usize>() {
+                    Ok(n) if (1..=3).contains(&n) => EquipSlot::all()[n - 1],
+                    _ => {
+                        println!("❌ 無効な入力です。");
+                        continue;
+                    }
+                };
 
-    for _ in 0..10 {
-        let name = encounter_pool.choose(&mut rng).unwrap();
-        if let Some(monster) = monsters.iter().find(|m| m.name == *name) {
-            selected.push(monster.clone());
+                match unequip(character, current, slot) {
+                    Some(id) => println!("✅ {}を外した。", session.content.name_of(&id)),
+                    None => println!("{}には何も装備していません。", slot.name()),
+                }
+            }
+            "0" => break,
+            _ => println!("❌ 無効な入力です。"),
         }
     }
 
-    selected
+    session.save(character);
+    Ok(())
 }
 
-fn select_mode() -> Mode {
+fn select_mode() -> Result<Mode, InputClosed> {
     println!("モードを選択してください:");
     println!("1. 通常モード");
     println!("2. ブーストモード");
     println!("3. ステージモード");
+    println!("4. 町のショップ");
+    println!("5. 装備");
 
-    let mut input = String::new();
-    stdin().read_line(&mut input).unwrap();
-    match input.trim() {
+    Ok(match read_input()?.as_str() {
         "2" => Mode::Boost,
         "3" => Mode::Stage,
+        "4" => Mode::Shop,
+        "5" => Mode::Equipment,
         _ => Mode::Normal,
-    }
-}
-
-// ステージが解放されているかをチェックする
-fn is_stage_unlocked(stage: Stage, character_lv: u32) -> bool {
-    character_lv >= stage.required_level()
+    })
 }
 
 // ステージ選択メニューを表示し、選択されたステージを返す
-fn select_stage(character: &Character) -> Option<Stage> {
+// メニューの番号は stages.yaml に並んでいる順番
+fn select_stage(character: &Character, stages: &[Stage]) -> Result<Option<Stage>, InputClosed> {
     println!("\n🗺️ ステージを選択してください:");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
-    for stage in Stage::all() {
-        let stage_num = stage as u32;
-        let unlocked = is_stage_unlocked(stage, character.lv);
-        let cleared = character.stages_cleared.contains(&stage_num);
+    for (i, stage) in stages.iter().enumerate() {
+        let unlocked = is_stage_unlocked(stage, character);
+        let cleared = character.stages_cleared.contains(&stage.id);
 
         if unlocked {
             let status = if cleared { "✅クリア済" } else { "" };
             println!(
                 "{}. {} (Lv{}～, バトル{}+ボス) {}",
-                stage_num,
-                stage.name().green(),
-                stage.required_level(),
-                stage.battle_count(),
+                i + 1,
+                stage.name.green(),
+                stage.unlock.level,
+                stage.battles,
                 status
             );
         } else {
+            // 先にクリアが必要なステージがあれば、その名前も表示する
+            let required: Vec<&str> = stage
+                .unlock
+                .cleared
+                .iter()
+                .filter_map(|id| find_stage(stages, *id))
+                .map(|s| s.name.as_str())
+                .collect();
+            let condition = if required.is_empty() {
+                format!("Lv{}で解放", stage.unlock.level)
+            } else {
+                format!(
+                    "Lv{}・{} クリアで解放",
+                    stage.unlock.level,
+                    required.join("・")
+                )
+            };
             println!(
-                "{}. {} {} (Lv{}で解放)",
-                stage_num,
-                stage.name().bright_black(),
+                "{}. {} {} ({})",
+                i + 1,
+                stage.name.bright_black(),
                 "🔒".bright_black(),
-                stage.required_level()
+                condition
             );
         }
+        if !stage.description.is_empty() {
+            println!("   {}", stage.description.bright_black());
+        }
     }
 
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!("0. 戻る");
     println!(
         "\n現在のレベル: {} | 経験値: {}",
-        character.lv, character.exp
+        character.hero.lv, character.hero.exp
     );
 
-    let mut input = String::new();
-    stdin().read_line(&mut input).unwrap();
-
-    match input.trim().parse::<u32>() {
+    Ok(match read_input()?.parse::<usize>() {
         Ok(0) => None,
-        Ok(n) if (1..=5).contains(&n) => {
-            if let Some(stage) = Stage::from_number(n) {
-                if is_stage_unlocked(stage, character.lv) {
-                    Some(stage)
-                } else {
-                    println!("❌ そのステージはまだ解放されていません。");
-                    None
-                }
+        Ok(n) if (1..=stages.len()).contains(&n) => {
+            let stage = &stages[n - 1];
+            if is_stage_unlocked(stage, character) {
+                Some(stage.clone())
             } else {
+                println!("❌ そのステージはまだ解放されていません。");
                 None
             }
         }
@@ -410,53 +1247,14 @@ fn select_stage(character: &Character) -> Option<Stage> {
             println!("❌ 無効な入力です。");
             None
         }
-    }
-}
-
-// 指定されたステージに出現する通常モンスターを取得
-fn get_stage_monsters(monsters: &[Monster], stage: Stage) -> Vec<Monster> {
-    let stage_num = stage as u32;
-    monsters
-        .iter()
-        .filter(|m| m.stage == stage_num && !m.is_boss)
-        .cloned()
-        .collect()
-}
-
-// 指定されたステージのボスモンスターを取得
-fn get_boss_monster(monsters: &[Monster], stage: Stage) -> Option<Monster> {
-    let stage_num = stage as u32;
-    monsters
-        .iter()
-        .find(|m| m.stage == stage_num && m.is_boss)
-        .cloned()
-}
-
-// ステージ用のバトルリストを生成（ランダムに通常モンスターを選択）
-fn choose_stage_monsters(monsters: &[Monster], stage: Stage) -> Vec<Monster> {
-    let stage_monsters = get_stage_monsters(monsters, stage);
-    let battle_count = stage.battle_count();
-
-    let mut rng = thread_rng();
-    let mut selected = Vec::with_capacity(battle_count);
-
-    for _ in 0..battle_count {
-        if let Some(monster) = stage_monsters.choose(&mut rng) {
-            selected.push(monster.clone());
-        }
-    }
-
-    selected
+    })
 }
 
-fn select_boost_rounds() -> usize {
+fn select_boost_rounds() -> Result<usize, InputClosed> {
     println!("Boost Battle 回数を選択してください:");
     println!("1. 10回\n2. 100回\n3. 500回\n4. 999回");
 
-    let mut input = String::new();
-    stdin().read_line(&mut input).unwrap();
-
-    match input.trim() {
+    Ok(match read_input()?.as_str() {
         "1" => 10,
         "2" => 100,
         "3" => 500,
@@ -465,149 +1263,253 @@ fn select_boost_rounds() -> usize {
             println!("無効な入力です。デフォルトの10回を選択します。");
             10
         }
+    })
+}
+
+// 全滅したときのペナルティを与えてセーブする
+// その場で復活して探索を続けられる場合は true（町に戻された場合やゲームオーバーは false）
+fn handle_defeat(character: &mut Character, session: &mut Session) -> bool {
+    for event in apply_defeat(character) {
+        session.emit(character, event);
     }
+    session.save(character);
+    character.defeat_rules.revives_on_spot()
 }
 
 // ステージモードのバトルを実行
-fn run_stage_mode(character: &mut Character, monsters: &[Monster], stage: Stage) {
-    let stage_num = stage as u32;
-    let total_battles = stage.battle_count();
-
-    println!("\n🏰 ステージ{}: {} に挑戦！", stage_num, stage.name());
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("バトル数: {} + ボス戦", total_battles);
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+fn run_stage_mode(
+    character: &mut Character,
+    stage: &Stage,
+    session: &mut Session,
+) -> Result<(), InputClosed> {
+    let stage_num = stage.id;
+    let total_battles = stage.battles;
+
+    session.emit(
+        character,
+        BattleEvent::StageStarted {
+            stage: stage_num,
+            name: stage.name.clone(),
+            battles: total_battles,
+        },
+    );
 
     // 現在挑戦中のステージを記録
     character.current_stage = Some(stage_num);
-    save_character(character).expect("セーブ中にエラーが発生しました。");
+    session.save(character);
 
     // 通常モンスターとのバトル
-    let stage_monsters = choose_stage_monsters(monsters, stage);
+    let stage_monsters = choose_stage_monsters(&session.content.monsters, stage, &mut session.rng);
 
-    for (i, monster) in stage_monsters.iter().enumerate() {
+    for (i, group) in stage_monsters.iter().enumerate() {
         println!(
             "\n📍 {} を探索中... (バトル {}/{})",
-            stage.name(),
+            stage.name,
             i + 1,
             total_battles
         );
-        thread::sleep(Duration::from_secs(2));
-
-        let win = battle(character, monster);
-        if !win {
-            println!("\n💀 ステージ{}で敗北...", stage.name());
-            character.hp = character.max_hp;
+        session.pause(2);
+
+        let result = battle(character, group, false, session)?;
+        // その場で起き上がるルールなら、ステージの探索を続ける
+        if result == BattleResult::Lose && character.defeat_rules.revives_on_spot() {
+            handle_defeat(character, session);
+        } else if result == BattleResult::Lose {
+            session.emit(
+                character,
+                BattleEvent::StageFailed {
+                    stage: stage_num,
+                    name: stage.name.clone(),
+                    boss: None,
+                },
+            );
             character.current_stage = None;
-            save_character(character).expect("セーブ中にエラーが発生しました。");
-            return;
+            handle_defeat(character, session);
+            return Ok(());
         }
 
+        field_menu(character, session)?;
+
         println!("🧭 先へ進む...");
-        thread::sleep(Duration::from_secs(3));
+        session.pause(3);
     }
 
     // ボス戦
     println!("\n⚠️ ボスエリアに到達！");
-    thread::sleep(Duration::from_secs(2));
+    session.pause(2);
 
-    if let Some(boss) = get_boss_monster(monsters, stage) {
+    if let Some(boss) = get_boss_monster(&session.content.monsters, stage) {
         println!("\n👹 ボス戦開始！");
         println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
-        let win = battle(character, &boss);
+        let result = battle(character, std::slice::from_ref(&boss), false, session)?;
 
-        if win {
-            println!("\n🎊 ステージ{}: {} クリア！", stage_num, stage.name());
-            println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        if result == BattleResult::Win {
+            session.emit(
+                character,
+                BattleEvent::StageCleared {
+                    stage: stage_num,
+                    name: stage.name.clone(),
+                },
+            );
 
-            // クリア済みステージに追加（重複チェック）
-            if !character.stages_cleared.contains(&stage_num) {
-                character.stages_cleared.push(stage_num);
-            }
+            character.mark_stage_cleared(stage_num);
             character.current_stage = None;
-            save_character(character).expect("セーブ中にエラーが発生しました。");
+            for event in recruit(character, &session.content.companions, stage_num) {
+                session.emit(character, event);
+            }
+            for event in restock(character) {
+                session.emit(character, event);
+            }
+            session.save(character);
 
             // 全ステージクリアチェック
-            if character.stages_cleared.len() == 5 {
+            if session
+                .content
+                .stages
+                .iter()
+                .all(|s| character.stages_cleared.contains(&s.id))
+            {
                 println!("\n🏆 おめでとうございます！全ステージクリア！");
                 println!("あなたは真の勇者です！");
             }
         } else {
-            println!("\n💀 ボス {} に敗北...", boss.name);
-            character.hp = character.max_hp;
+            session.emit(
+                character,
+                BattleEvent::StageFailed {
+                    stage: stage_num,
+                    name: stage.name.clone(),
+                    boss: Some(boss.name.clone()),
+                },
+            );
             character.current_stage = None;
-            save_character(character).expect("セーブ中にエラーが発生しました。");
+            handle_defeat(character, session);
         }
     } else {
         println!("❌ ボスモンスターが見つかりませんでした。");
         character.current_stage = None;
-        save_character(character).expect("セーブ中にエラーが発生しました。");
+        session.save(character);
     }
+    Ok(())
 }
 
-fn main() {
-    let mut character = load_or_create_character().expect("キャラクターの読み込みに失敗しました。");
-    let monsters = load_monsters().expect("モンスターの読み込みに失敗しました");
+// データファイルを検証し、見つかった問題をすべて表示する（kt_rpg validate）
+// 問題があれば終了コード 1 で終わる
+fn run_validate() -> ! {
+    println!("🔍 データファイルを検証しています...");
+    let problems = validate_content();
+
+    if problems.is_empty() {
+        println!("{}", "✅ 問題は見つかりませんでした。".green());
+        process::exit(0);
+    }
 
-    match select_mode() {
+    for problem in &problems {
+        println!("❌ {}", problem);
+    }
+    println!(
+        "{}",
+        format!("⚠️ {}件の問題が見つかりました。", problems.len()).red()
+    );
+    process::exit(1);
+}
+
+// モードを選ばせて遊ぶ
+fn play(character: &mut Character, session: &mut Session) -> Result<(), InputClosed> {
+    // ショップと装備はモード選択に戻る
+    let mode = loop {
+        match select_mode()? {
+            Mode::Shop => run_shop(character, session)?,
+            Mode::Equipment => run_equipment_menu(character, session)?,
+            mode => break mode,
+        }
+    };
+
+    match mode {
         Mode::Normal => {
-            let weighted_monsters: Vec<Monster> = choose_monsters(&monsters);
+            let groups = match choose_monsters(
+                &session.content.monsters,
+                session.encounter,
+                &mut session.rng,
+            ) {
+                Ok(groups) => groups,
+                Err(e) => {
+                    println!("❌ {}", e);
+                    Vec::new()
+                }
+            };
 
-            for monster in weighted_monsters.iter() {
-                let win = battle(&mut character, monster);
-                if !win {
-                    println!("ゲームオーバー⚰️");
+            for group in groups.iter() {
+                let result = battle(character, group, false, session)?;
+                if result == BattleResult::Lose && !handle_defeat(character, session) {
                     break;
-                } else {
-                    println!("ダンジョンを探索中🧭");
-                    thread::sleep(Duration::from_secs(10));
                 }
+                println!("ダンジョンを探索中🧭");
+                session.pause(10);
             }
         }
         Mode::Boost => {
-            let rounds = select_boost_rounds();
+            let rounds = select_boost_rounds()?;
             let mut victories = 0;
 
             for i in 0..rounds {
-                let monster = choose_monsters(&monsters)
-                    .first()
-                    .expect("モンスターが見つかりませんでした。")
-                    .clone();
+                let group = match choose_monsters(
+                    &session.content.monsters,
+                    session.encounter,
+                    &mut session.rng,
+                ) {
+                    Ok(groups) => groups.into_iter().next(),
+                    Err(e) => {
+                        println!("❌ {}", e);
+                        None
+                    }
+                };
+                let Some(group) = group else {
+                    break;
+                };
 
                 println!("\n🔥 Boostバトル {} / {}:", i + 1, rounds);
-                let win = battle(&mut character, &monster);
+                // ブーストモードは従来どおり自動で戦う
+                let result = battle(character, &group, true, session)?;
 
-                if win {
+                if result == BattleResult::Win {
                     victories += 1;
                     println!("🎉 勝利！累計勝利数: {}", victories);
                 } else {
+                    handle_defeat(character, session);
+                    if character.game_over {
+                        break;
+                    }
                     println!("😵‍💫 敗北... でも再挑戦します！");
                 }
 
-                character.hp = character.max_hp;
-                save_character(&character).expect("セーブ中にエラーが発生しました。");
+                character.rest();
+                session.save(character);
 
-                thread::sleep(Duration::from_secs(1));
+                session.pause(1);
             }
 
             println!(
                 "\n🚩 Boost Battle 終了！総勝利数: {} / {} | 最終レベル: {} | 経験値: {}\n",
-                victories, rounds, character.lv, character.exp
+                victories, rounds, character.hero.lv, character.hero.exp
             );
         }
+        Mode::Shop | Mode::Equipment => unreachable!(),
         Mode::Stage => {
             // ステージ選択ループ
             loop {
-                if let Some(stage) = select_stage(&character) {
-                    run_stage_mode(&mut character, &monsters, stage);
-
-                    println!("\n続けますか？ (y/n)");
-                    let mut input = String::new();
-                    stdin().read_line(&mut input).unwrap();
-                    if input.trim().to_lowercase() != "y" {
+                if let Some(stage) = select_stage(character, &session.content.stages)? {
+                    run_stage_mode(character, &stage, session)?;
+                    if character.game_over {
                         break;
                     }
+
+                    println!("\n続けますか？ (y/n, s: 町のショップに寄ってから続ける)");
+                    match read_input()?.to_lowercase().as_str() {
+                        "y" => {}
+                        "s" => run_shop(character, session)?,
+                        _ => break,
+                    }
                 } else {
                     println!("ステージモードを終了します。");
                     break;
@@ -615,4 +1517,82 @@ fn main() {
             }
         }
     }
+
+    Ok(())
+}
+
+fn main() {
+    let options = parse_args();
+    if options.validate {
+        run_validate();
+    }
+
+    match migrate_legacy_save() {
+        Ok(Some(slot)) => println!(
+            "📦 {} をスロット {} に移行しました。",
+            LEGACY_SAVE_PATH, slot
+        ),
+        Ok(None) => {}
+        Err(e) => println!("❌ 旧セーブの移行に失敗しました: {}", e),
+    }
+
+    // スロット一覧でステージ数を表示するため、先にゲームデータを読み込む
+    println!("モンスターのデータを読み込み中...");
+    let mut content = match Content::load() {
+        Ok(content) => content,
+        Err(e) => {
+            println!("{}", "❌ ゲームデータの読み込みに失敗しました。".red());
+            println!("{}", e);
+            println!("💡 kt_rpg validate ですべての問題を確認できます。");
+            process::exit(1);
+        }
+    };
+
+    let Ok(Some((slot, mut character))) = select_slot(&content.stages) else {
+        return;
+    };
+
+    // シード未指定ならランダムに決め、再現用にセーブへ記録する
+    let seed = options.seed.unwrap_or_else(rand::random);
+    // 進行速度はコマンドライン引数 > config.yaml > 通常 の順で決める
+    let config = load_config().unwrap_or_else(|e| {
+        println!("❌ 設定ファイルの読み込みに失敗しました: {}", e);
+        Default::default()
+    });
+    let speed = options.speed.unwrap_or(config.speed);
+    // config.yaml の出現しやすさの表で上書きし、存在しないモンスター名は警告する
+    for name in apply_encounter_weights(&mut content.monsters, &config.encounter_weights) {
+        println!(
+            "⚠️ {} の encounter_weights にある「{}」というモンスターは見つかりません。",
+            CONFIG_PATH, name
+        );
+    }
+
+    let mut session = Session {
+        slot,
+        content,
+        encounter: config.encounter,
+        rng: GameRng::seed_from_u64(seed),
+        log: BattleLog::new(),
+        pacer: Box::new(SpeedPacer::new(speed)),
+    };
+    println!("🎲 乱数シード: {}", seed);
+    character.seed = Some(seed);
+    // スキル導入前のセーブでも、今のレベルまでに覚えるスキルを覚えさせる
+    for id in learn_skills(&mut character.hero, &session.content.skills) {
+        if let Some(skill) = find_skill(&session.content.skills, &id) {
+            println!("📖{}を覚えた！", skill.name);
+        }
+    }
+    session.save(&character);
+
+    // 入力が閉じられた場合（EOF）も、ここまでのイベントログは書き出す
+    let _ = play(&mut character, &mut session);
+
+    if let Some(path) = &options.event_log {
+        match write_event_log(&session.log, path, options.event_filter.as_deref()) {
+            Ok(()) => println!("📝 イベントログを {} に書き出しました。", path),
+            Err(e) => println!("❌ イベントログの書き出しに失敗しました: {}", e),
+        }
+    }
 }
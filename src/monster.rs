@@ -0,0 +1,422 @@
+use std::{collections::BTreeMap, path::Path};
+
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ability::{validate_abilities, Ability, BehaviorRule},
+    character::default_speed,
+    element::{Affinity, Element},
+    error::{entry_line, load_list, GameError, Result},
+    stage::{EncounterRule, Stage},
+    status::Inflict,
+    GameRng,
+};
+
+// モンスターデータのパス
+pub const MONSTERS_PATH: &str = "monsters.yaml";
+
+// デフォルトのステージ値（互換性のため）
+fn default_stage() -> u32 {
+    1
+}
+
+// デフォルトのレベル（互換性のため）
+fn default_level() -> u32 {
+    1
+}
+
+// デフォルトのドロップ個数（互換性のため）
+fn default_count() -> u32 {
+    1
+}
+
+// デフォルトのドロップ確率（テーブルがあれば必ず1つ落とす）
+fn default_drop_chance() -> f64 {
+    1.0
+}
+
+// 落とすアイテムと個数
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ItemDrop {
+    pub item: String, // アイテムID
+    #[serde(default = "default_count")]
+    pub count: u32,
+}
+
+// ドロップテーブルの1項目
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DropEntry {
+    pub item: String, // アイテムID
+    pub weight: u32,  // 抽選の重み
+    #[serde(default = "default_count")]
+    pub count: u32,
+}
+
+// 倒したときに得られる報酬
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Loot {
+    pub gold: u32,
+    pub items: Vec<ItemDrop>,
+}
+
+// ボスの2つ目以降の形態
+// HPが0になると次の形態に変わる。省略した項目は前の形態のものを引き継ぐ
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Phase {
+    // 形態の名前（省略するとモンスター名のまま）
+    #[serde(default)]
+    pub name: Option<String>,
+    pub hp: u32,
+    pub min_attack: u32,
+    pub max_attack: u32,
+    // 形態が変わるときのセリフ
+    #[serde(default)]
+    pub dialogue: Vec<String>,
+    #[serde(default)]
+    pub element: Option<Element>,
+    #[serde(default)]
+    pub weaknesses: Option<Vec<Element>>,
+    #[serde(default)]
+    pub resistances: Option<Vec<Element>>,
+    #[serde(default)]
+    pub inflicts: Option<Vec<Inflict>>,
+    #[serde(default)]
+    pub abilities: Option<Vec<Ability>>,
+    #[serde(default)]
+    pub behavior: Option<Vec<BehaviorRule>>,
+}
+
+// モンスター構造体
+// ステージシステム対応のため、stageとis_bossを追加
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Monster {
+    pub name: String,
+    pub hp: u32,
+    pub max_hp: u32,
+    pub min_attack: u32,
+    pub max_attack: u32,
+    pub exp: u32,
+    // レベル（逃げられる確率の計算に使う）
+    #[serde(default = "default_level")]
+    pub lv: u32,
+    // 素早さ（高い順にバトルで行動する。逃げられる確率にも影響する）
+    #[serde(default = "default_speed")]
+    pub speed: u32,
+    // 通常モード・ブーストモードでの出現しやすさ（0 なら出現しない）
+    #[serde(default)]
+    pub encounter_weight: u32,
+    // 所属ステージの id (stages.yaml)、互換性のためデフォルト値を設定
+    #[serde(default = "default_stage")]
+    pub stage: u32,
+    // ボスモンスターフラグ
+    #[serde(default)]
+    pub is_boss: bool,
+    // ドロップ判定に当たる確率 (0.0-1.0)、当たれば drops から重みに従って1つ選ぶ
+    #[serde(default = "default_drop_chance")]
+    pub drop_chance: f64,
+    // ドロップテーブル（互換性のため、無ければ何も落とさない）
+    #[serde(default)]
+    pub drops: Vec<DropEntry>,
+    // 必ず落とすアイテム（主にボス用）
+    #[serde(default)]
+    pub guaranteed_drops: Vec<ItemDrop>,
+    // 落とすゴールドの範囲
+    #[serde(default)]
+    pub min_gold: u32,
+    #[serde(default)]
+    pub max_gold: u32,
+    // モンスター自身の属性（同じ属性の攻撃には耐性がある）
+    #[serde(default)]
+    pub element: Option<Element>,
+    // 弱点の属性（ダメージ1.5倍）
+    #[serde(default)]
+    pub weaknesses: Vec<Element>,
+    // 耐性のある属性（ダメージ半減）
+    #[serde(default)]
+    pub resistances: Vec<Element>,
+    // 攻撃が当たったときに与える状態異常
+    #[serde(default)]
+    pub inflicts: Vec<Inflict>,
+    // 特技（無ければ通常攻撃だけを行う）
+    #[serde(default)]
+    pub abilities: Vec<Ability>,
+    // 特技を使う条件（上から順に判定する）
+    #[serde(default)]
+    pub behavior: Vec<BehaviorRule>,
+    // 2つ目以降の形態（主にボス用、経験値とドロップは最後の形態を倒したときに得る）
+    #[serde(default)]
+    pub phases: Vec<Phase>,
+}
+
+impl Monster {
+    // 次の形態に変わる（HPは新しい形態の値に戻る）
+    pub fn transform(&mut self, phase: &Phase) {
+        if let Some(name) = &phase.name {
+            self.name = name.clone();
+        }
+        self.hp = phase.hp;
+        self.max_hp = phase.hp;
+        self.min_attack = phase.min_attack;
+        self.max_attack = phase.max_attack;
+        if phase.element.is_some() {
+            self.element = phase.element;
+        }
+        if let Some(weaknesses) = &phase.weaknesses {
+            self.weaknesses = weaknesses.clone();
+        }
+        if let Some(resistances) = &phase.resistances {
+            self.resistances = resistances.clone();
+        }
+        if let Some(inflicts) = &phase.inflicts {
+            self.inflicts = inflicts.clone();
+        }
+        if let Some(abilities) = &phase.abilities {
+            self.abilities = abilities.clone();
+        }
+        if let Some(behavior) = &phase.behavior {
+            self.behavior = behavior.clone();
+        }
+    }
+
+    // 指定した属性の攻撃との相性を返す
+    pub fn affinity(&self, element: Element) -> Affinity {
+        if self.weaknesses.contains(&element) {
+            Affinity::Effective
+        } else if self.resistances.contains(&element) || self.element == Some(element) {
+            Affinity::Resisted
+        } else {
+            Affinity::Normal
+        }
+    }
+}
+
+pub fn load_monsters() -> Result<Vec<Monster>> {
+    load_list(MONSTERS_PATH, check_monsters)
+}
+
+// モンスター名の重複と、報酬・特技・形態の値を確認する
+pub(crate) fn check_monsters(data: &str, monsters: &[Monster]) -> Vec<GameError> {
+    let path = Path::new(MONSTERS_PATH);
+    let mut problems = Vec::new();
+
+    // ボスや召喚はモンスター名で参照するので、名前が重複してはいけない
+    let names: Vec<&str> = monsters.iter().map(|m| m.name.as_str()).collect();
+    for (i, monster) in monsters.iter().enumerate() {
+        let line = entry_line(data, i);
+        if names[..i].contains(&monster.name.as_str()) {
+            problems.push(
+                GameError::validation(
+                    path,
+                    format!("モンスター {} が重複しています", monster.name),
+                )
+                .at_line(line),
+            );
+        }
+        for message in validate_monster(monster, &names) {
+            problems.push(
+                GameError::validation(path, format!("{}: {}", monster.name, message)).at_line(line),
+            );
+        }
+    }
+
+    problems
+}
+
+// バトル中に panic しないよう、HP や攻撃力の範囲を確認し、見つかった問題をすべて返す
+fn validate_monster(monster: &Monster, names: &[&str]) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if monster.min_gold > monster.max_gold {
+        problems.push(format!(
+            "min_gold ({}) が max_gold ({}) より大きくなっています",
+            monster.min_gold, monster.max_gold
+        ));
+    }
+    if !(0.0..=1.0).contains(&monster.drop_chance) {
+        problems.push(format!(
+            "drop_chance ({}) は 0.0 から 1.0 の範囲である必要があります",
+            monster.drop_chance
+        ));
+    }
+    if !monster.drops.is_empty() && monster.drops.iter().all(|d| d.weight == 0) {
+        problems.push("drops の weight が全て0です".to_string());
+    }
+    problems.extend(validate_form(monster, names));
+
+    // 形態ごとに、変わった後のモンスターとして形態で変わる項目を確認する
+    let mut form = monster.clone();
+    for (i, phase) in monster.phases.iter().enumerate() {
+        form.transform(phase);
+        problems.extend(
+            validate_form(&form, names)
+                .into_iter()
+                .map(|message| format!("形態{}: {}", i + 2, message)),
+        );
+    }
+
+    problems
+}
+
+// 形態ごとに変わる HP・攻撃力・属性・状態異常・特技を確認する
+fn validate_form(monster: &Monster, names: &[&str]) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if monster.max_hp == 0 {
+        problems.push("max_hp は1以上である必要があります".to_string());
+    }
+    if monster.hp != monster.max_hp {
+        problems.push(format!(
+            "hp ({}) は max_hp ({}) と同じである必要があります",
+            monster.hp, monster.max_hp
+        ));
+    }
+    if monster.min_attack > monster.max_attack {
+        problems.push(format!(
+            "min_attack ({}) が max_attack ({}) より大きくなっています",
+            monster.min_attack, monster.max_attack
+        ));
+    }
+    problems.extend(monster.inflicts.iter().filter_map(|i| i.validate().err()));
+    if let Err(message) = validate_abilities(&monster.abilities, &monster.behavior, names) {
+        problems.push(message);
+    }
+    if let Some(element) = monster
+        .weaknesses
+        .iter()
+        .find(|e| monster.resistances.contains(e))
+    {
+        problems.push(format!(
+            "{}属性が weaknesses と resistances の両方に含まれています",
+            element.name()
+        ));
+    }
+
+    problems
+}
+
+// 倒したときの報酬（ゴールドとドロップアイテム）を決める
+pub fn roll_loot(monster: &Monster, rng: &mut GameRng) -> Loot {
+    let gold = rng.gen_range(monster.min_gold..=monster.max_gold);
+    let mut items = monster.guaranteed_drops.clone();
+
+    if !monster.drops.is_empty() && rng.gen_bool(monster.drop_chance) {
+        if let Ok(entry) = monster.drops.choose_weighted(rng, |d| d.weight) {
+            items.push(ItemDrop {
+                item: entry.item.clone(),
+                count: entry.count,
+            });
+        }
+    }
+
+    Loot { gold, items }
+}
+
+// 候補（モンスターと重み）から、重みに従って群れを1つ組む
+pub fn choose_group(
+    candidates: &[(&Monster, u32)],
+    rule: EncounterRule,
+    rng: &mut GameRng,
+) -> Vec<Monster> {
+    let size = rng.gen_range(rule.min_enemies..=rule.max_enemies);
+    let mut group: Vec<Monster> = Vec::with_capacity(size);
+
+    for _ in 0..size {
+        // 同じ種類が max_same に達したものは候補から外す
+        let available: Vec<(&Monster, u32)> = candidates
+            .iter()
+            .copied()
+            .filter(|(c, _)| group.iter().filter(|m| m.name == c.name).count() < rule.max_same)
+            .collect();
+        // 重みの合計が u32 を超えないよう f64 で抽選する（重みが全て0なら選べない）
+        match available.choose_weighted(rng, |(_, weight)| f64::from(*weight)) {
+            Ok((monster, _)) => group.push((*monster).clone()),
+            Err(_) => break,
+        }
+    }
+
+    group
+}
+
+// 出現しやすさの表（モンスター名 → encounter_weight）で monsters.yaml の値を上書きする
+// 読み込んだモンスターに見つからなかった名前を返す（呼び出し側で警告する）
+pub fn apply_encounter_weights(
+    monsters: &mut [Monster],
+    weights: &BTreeMap<String, u32>,
+) -> Vec<String> {
+    let mut unmatched = Vec::new();
+
+    for (name, weight) in weights {
+        match monsters.iter_mut().find(|m| m.name == *name) {
+            Some(monster) => monster.encounter_weight = *weight,
+            None => unmatched.push(name.clone()),
+        }
+    }
+
+    unmatched
+}
+
+// 通常モード・ブーストモードで出現するモンスターがいるかを確認する
+pub fn check_encounter_pool(monsters: &[Monster]) -> Result<()> {
+    if monsters.iter().any(|m| m.encounter_weight > 0) {
+        return Ok(());
+    }
+
+    Err(GameError::validation(
+        MONSTERS_PATH,
+        "encounter_weight が1以上のモンスターがいないので、通常モード・ブーストモードで出現するモンスターがいません",
+    ))
+}
+
+// 通常モード用に10回分のモンスターの群れを選ぶ
+// encounter_weight が重いものほど出やすい（0 のものは出ない）
+// 群れの組み方は config.yaml の encounter で決める（省略すると1体ずつ）
+pub fn choose_monsters(
+    monsters: &[Monster],
+    rule: EncounterRule,
+    rng: &mut GameRng,
+) -> Result<Vec<Vec<Monster>>> {
+    check_encounter_pool(monsters)?;
+
+    let candidates: Vec<(&Monster, u32)> = monsters
+        .iter()
+        .filter(|m| m.encounter_weight > 0)
+        .map(|m| (m, m.encounter_weight))
+        .collect();
+
+    Ok((0..10)
+        .map(|_| choose_group(&candidates, rule, rng))
+        .filter(|group| !group.is_empty())
+        .collect())
+}
+
+// 指定されたステージに出現する通常モンスターを取得
+pub fn get_stage_monsters(monsters: &[Monster], stage: &Stage) -> Vec<Monster> {
+    monsters
+        .iter()
+        .filter(|m| m.stage == stage.id && !m.is_boss)
+        .cloned()
+        .collect()
+}
+
+// 指定されたステージのボスモンスターを取得
+pub fn get_boss_monster(monsters: &[Monster], stage: &Stage) -> Option<Monster> {
+    monsters.iter().find(|m| m.name == stage.boss).cloned()
+}
+
+// ステージ用のバトルリストを生成（ステージの組み方に従って通常モンスターの群れを選択）
+pub fn choose_stage_monsters(
+    monsters: &[Monster],
+    stage: &Stage,
+    rng: &mut GameRng,
+) -> Vec<Vec<Monster>> {
+    let stage_monsters = get_stage_monsters(monsters, stage);
+    // ステージのモンスターは同じ確率で出る
+    let candidates: Vec<(&Monster, u32)> = stage_monsters.iter().map(|m| (m, 1)).collect();
+    let rule = stage.encounter;
+
+    (0..stage.battles)
+        .map(|_| choose_group(&candidates, rule, rng))
+        .filter(|group| !group.is_empty())
+        .collect()
+}
@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    battle::BattleEvent,
+    character::{Character, PartyMember, Stats, MAX_PARTY_SIZE},
+    equipment::Equipped,
+    error::{entry_line, load_list, GameError, Result},
+};
+
+// 仲間データのパス
+pub const COMPANIONS_PATH: &str = "companions.yaml";
+
+// 仲間にできるキャラクター
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompanionDef {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    // このステージをクリアすると仲間になる
+    pub join_stage: u32,
+    // 仲間になったときのレベルと能力値
+    pub lv: u32,
+    pub hp: u32,
+    pub mp: u32,
+    pub stats: Stats,
+    // 最初から使えるスキルのID（仲間はレベルアップで新しいスキルを覚えない）
+    #[serde(default)]
+    pub skills: Vec<String>,
+}
+
+impl CompanionDef {
+    // 仲間になったときのメンバーを作る
+    pub fn to_member(&self) -> PartyMember {
+        PartyMember {
+            name: self.name.clone(),
+            lv: self.lv,
+            hp: self.hp,
+            max_hp: self.hp,
+            stats: self.stats.clone(),
+            exp: 0,
+            mp: self.mp,
+            max_mp: self.mp,
+            equipment: Equipped::default(),
+            skills: self.skills.clone(),
+            status: Vec::new(),
+        }
+    }
+}
+
+pub fn load_companions() -> Result<Vec<CompanionDef>> {
+    load_list(COMPANIONS_PATH, check_companions)
+}
+
+// 仲間の重複と能力値を確認する
+pub(crate) fn check_companions(data: &str, companions: &[CompanionDef]) -> Vec<GameError> {
+    let path = Path::new(COMPANIONS_PATH);
+    let mut problems = Vec::new();
+
+    for (i, companion) in companions.iter().enumerate() {
+        let line = entry_line(data, i);
+        if companions[..i]
+            .iter()
+            .any(|other| other.name == companion.name)
+        {
+            problems.push(
+                GameError::validation(path, format!("仲間 {} が重複しています", companion.name))
+                    .at_line(line),
+            );
+        }
+        if companion.hp == 0 || companion.stats.min_attack > companion.stats.max_attack {
+            problems.push(
+                GameError::validation(
+                    path,
+                    format!(
+                        "{}: hp は1以上、min_attack は max_attack 以下である必要があります",
+                        companion.name
+                    ),
+                )
+                .at_line(line),
+            );
+        }
+        if companion.stats.min_recovery > companion.stats.max_recovery {
+            problems.push(
+                GameError::validation(
+                    path,
+                    format!(
+                        "{}: min_recovery が max_recovery より大きくなっています",
+                        companion.name
+                    ),
+                )
+                .at_line(line),
+            );
+        }
+    }
+
+    problems
+}
+
+// ステージクリア時に、そのステージで仲間になるキャラクターを加える
+pub fn recruit(
+    character: &mut Character,
+    companions: &[CompanionDef],
+    stage_num: u32,
+) -> Vec<BattleEvent> {
+    let mut events = Vec::new();
+
+    for companion in companions.iter().filter(|c| c.join_stage == stage_num) {
+        if character
+            .companions
+            .iter()
+            .any(|m| m.name == companion.name)
+        {
+            continue;
+        }
+        if character.party_size() >= MAX_PARTY_SIZE {
+            events.push(BattleEvent::PartyFull {
+                name: companion.name.clone(),
+            });
+            continue;
+        }
+        character.companions.push(companion.to_member());
+        events.push(BattleEvent::MemberJoined {
+            name: companion.name.clone(),
+        });
+    }
+
+    events
+}
@@ -0,0 +1,134 @@
+use std::{fmt, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    battle::BattleEvent,
+    character::Character,
+    error::{entry_line, load_list, GameError, Result},
+};
+
+// ショップデータのパス
+pub const SHOP_PATH: &str = "shop.yaml";
+
+// ショップの商品
+// stock を省略すると在庫は無制限。残りの在庫はキャラクターごとに保存され、ステージをクリアすると data の値に戻る
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShopEntry {
+    pub item: String, // アイテムID
+    pub price: u32,   // 購入価格
+    #[serde(default)]
+    pub stock: Option<u32>,
+}
+
+impl ShopEntry {
+    // 今の在庫（None なら無制限）
+    pub fn stock_left(&self, character: &Character) -> Option<u32> {
+        self.stock.map(|stock| {
+            character
+                .shop_stock
+                .get(&self.item)
+                .copied()
+                .unwrap_or(stock)
+        })
+    }
+}
+
+// 売値（アイテム・装備の値段の半額）
+pub fn sell_price(price: u32) -> u32 {
+    price / 2
+}
+
+// 売買できなかった理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShopError {
+    NotEnoughGold, // ゴールドが足りない
+    OutOfStock,    // 在庫切れ
+    NotOwned,      // 売ろうとしたアイテムを持っていない
+    NotForSale,    // 値段が無く売れない
+}
+
+impl fmt::Display for ShopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShopError::NotEnoughGold => write!(f, "ゴールドが足りません"),
+            ShopError::OutOfStock => write!(f, "在庫がありません"),
+            ShopError::NotOwned => write!(f, "そのアイテムを持っていません"),
+            ShopError::NotForSale => write!(f, "そのアイテムは売れません"),
+        }
+    }
+}
+
+pub fn load_shop() -> Result<Vec<ShopEntry>> {
+    load_list(SHOP_PATH, check_shop)
+}
+
+// 同じ商品が2回並んでいないかを確認する
+pub(crate) fn check_shop(data: &str, entries: &[ShopEntry]) -> Vec<GameError> {
+    let path = Path::new(SHOP_PATH);
+    let mut problems = Vec::new();
+
+    for (i, entry) in entries.iter().enumerate() {
+        if entries[..i].iter().any(|other| other.item == entry.item) {
+            problems.push(
+                GameError::validation(path, format!("商品 {} が重複しています", entry.item))
+                    .at_line(entry_line(data, i)),
+            );
+        }
+    }
+
+    problems
+}
+
+// 1個買う
+pub fn buy(character: &mut Character, entry: &ShopEntry) -> std::result::Result<(), ShopError> {
+    let stock = entry.stock_left(character);
+    if stock == Some(0) {
+        return Err(ShopError::OutOfStock);
+    }
+    if character.gold < entry.price {
+        return Err(ShopError::NotEnoughGold);
+    }
+
+    character.gold -= entry.price;
+    character.inventory.add(&entry.item, 1);
+    if let Some(stock) = stock {
+        character.shop_stock.insert(entry.item.clone(), stock - 1);
+    }
+    Ok(())
+}
+
+// 所持品を1個売り、得たゴールドを返す
+// price はアイテム・装備の値段（Content::price_of）。店に並んでいない戦利品も売れる
+// 在庫に限りのある商品なら、売ったものは在庫に戻る
+pub fn sell(
+    character: &mut Character,
+    entries: &[ShopEntry],
+    id: &str,
+    price: u32,
+) -> std::result::Result<u32, ShopError> {
+    if price == 0 {
+        return Err(ShopError::NotForSale);
+    }
+    if !character.inventory.remove(id) {
+        return Err(ShopError::NotOwned);
+    }
+
+    let gold = sell_price(price);
+    character.gold += gold;
+    if let Some(entry) = entries.iter().find(|entry| entry.item == id) {
+        if let Some(stock) = entry.stock_left(character) {
+            character.shop_stock.insert(entry.item.clone(), stock + 1);
+        }
+    }
+    Ok(gold)
+}
+
+// ステージをクリアしたときに、在庫を shop.yaml の値に戻す
+pub fn restock(character: &mut Character) -> Vec<BattleEvent> {
+    if character.shop_stock.is_empty() {
+        return Vec::new();
+    }
+    character.shop_stock.clear();
+    vec![BattleEvent::ShopRestocked]
+}
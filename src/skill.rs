@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    character::PartyMember,
+    element::Element,
+    error::{entry_line, load_list, GameError, Result},
+    status::Inflict,
+};
+
+// スキルデータのパス
+pub const SKILLS_PATH: &str = "skills.yaml";
+
+// スキルの効果
+// YAML では type フィールドで種類を指定する
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SkillEffect {
+    // 通常攻撃を hits 回くり返す
+    MultiHit {
+        hits: u32,
+    },
+    // 通常攻撃の power% のダメージを与える
+    HeavyStrike {
+        power: u32,
+    },
+    // 属性魔法（攻撃力に依らず、min_damage～max_damage にレベル×2を加えたダメージ）
+    Spell {
+        element: Element,
+        min_damage: u32,
+        max_damage: u32,
+    },
+    // 数ターンの間、毎ターンHPを回復する
+    Regen {
+        amount: u32,
+        turns: u32,
+    },
+    // 数ターンの間、攻撃力・防御力を上げる
+    Buff {
+        #[serde(default)]
+        attack: u32,
+        #[serde(default)]
+        defense: u32,
+        turns: u32,
+    },
+    // 相手を状態異常にする
+    Inflict(Inflict),
+}
+
+impl SkillEffect {
+    // モンスター1体を対象に選ぶ効果か（回復や強化は自分にかかる）
+    pub fn targets_enemy(&self) -> bool {
+        !matches!(self, SkillEffect::Regen { .. } | SkillEffect::Buff { .. })
+    }
+}
+
+// スキル構造体
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Skill {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub mp_cost: u32,
+    // このレベルに達すると覚える
+    pub learn_level: u32,
+    pub effect: SkillEffect,
+}
+
+pub fn load_skills() -> Result<Vec<Skill>> {
+    load_list(SKILLS_PATH, check_skills)
+}
+
+// スキルIDの重複と効果の値を確認する
+pub(crate) fn check_skills(data: &str, skills: &[Skill]) -> Vec<GameError> {
+    let path = Path::new(SKILLS_PATH);
+    let mut problems = Vec::new();
+
+    for (i, skill) in skills.iter().enumerate() {
+        let line = entry_line(data, i);
+        if skills[..i].iter().any(|other| other.id == skill.id) {
+            problems.push(
+                GameError::validation(path, format!("スキルID {} が重複しています", skill.id))
+                    .at_line(line),
+            );
+        }
+        if let Err(message) = validate_skill(skill) {
+            problems.push(
+                GameError::validation(path, format!("{}: {}", skill.id, message)).at_line(line),
+            );
+        }
+    }
+
+    problems
+}
+
+// バトル中に panic しないよう、回数やダメージの範囲を確認する
+fn validate_skill(skill: &Skill) -> std::result::Result<(), String> {
+    match skill.effect {
+        SkillEffect::MultiHit { hits: 0 } => Err("hits は1以上である必要があります".to_string()),
+        SkillEffect::Spell {
+            min_damage,
+            max_damage,
+            ..
+        } if min_damage > max_damage => Err(format!(
+            "min_damage ({}) が max_damage ({}) より大きくなっています",
+            min_damage, max_damage
+        )),
+        SkillEffect::Regen { turns: 0, .. } | SkillEffect::Buff { turns: 0, .. } => {
+            Err("turns は1以上である必要があります".to_string())
+        }
+        SkillEffect::Inflict(inflict) => inflict.validate(),
+        _ => Ok(()),
+    }
+}
+
+// IDからスキルを探す
+pub fn find_skill<'a>(skills: &'a [Skill], id: &str) -> Option<&'a Skill> {
+    skills.iter().find(|skill| skill.id == id)
+}
+
+// 現在のレベルで覚えられるスキルをすべて覚え、新しく覚えたスキルのIDを返す
+pub fn learn_skills(member: &mut PartyMember, skills: &[Skill]) -> Vec<String> {
+    let mut learned = Vec::new();
+
+    for skill in skills {
+        if skill.learn_level <= member.lv && !member.skills.contains(&skill.id) {
+            member.skills.push(skill.id.clone());
+            learned.push(skill.id.clone());
+        }
+    }
+
+    learned
+}
@@ -0,0 +1,161 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    battle::MAX_ENEMIES,
+    character::Character,
+    error::{entry_line, load_list, GameError, Result},
+    monster::{Monster, MONSTERS_PATH},
+};
+
+// ステージデータのパス
+pub const STAGES_PATH: &str = "stages.yaml";
+
+// 1回のバトルで現れるモンスターの群れの組み方
+// 互換性のため、省略すると従来どおり1体ずつ現れる
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncounterRule {
+    pub min_enemies: usize, // 群れの最小数
+    pub max_enemies: usize, // 群れの最大数（MAX_ENEMIES 以下）
+    pub max_same: usize,    // 同じ種類のモンスターが並べる数
+}
+
+impl Default for EncounterRule {
+    fn default() -> Self {
+        EncounterRule {
+            min_enemies: 1,
+            max_enemies: 1,
+            max_same: 1,
+        }
+    }
+}
+
+// ステージに挑戦するための条件
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Unlock {
+    // 必要なレベル
+    #[serde(default)]
+    pub level: u32,
+    // 先にクリアしておく必要があるステージの id
+    #[serde(default)]
+    pub cleared: Vec<u32>,
+}
+
+// ステージ構造体
+// 各ステージには解放条件、バトル数、ボス、モンスターの群れの組み方がある
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Stage {
+    pub id: u32,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub unlock: Unlock,
+    // ボス戦の前の通常バトルの数
+    pub battles: usize,
+    // ボスのモンスター名
+    pub boss: String,
+    #[serde(default)]
+    pub encounter: EncounterRule,
+}
+
+pub fn load_stages(monsters: &[Monster]) -> Result<Vec<Stage>> {
+    load_list(STAGES_PATH, |data, stages| {
+        check_stages(data, stages, monsters)
+    })
+}
+
+// ステージ番号の重複と、群れの組み方・ボス・解放条件を確認する
+pub(crate) fn check_stages(data: &str, stages: &[Stage], monsters: &[Monster]) -> Vec<GameError> {
+    let path = Path::new(STAGES_PATH);
+    let mut problems = Vec::new();
+
+    for (i, stage) in stages.iter().enumerate() {
+        let line = entry_line(data, i);
+        if stages[..i].iter().any(|other| other.id == stage.id) {
+            problems.push(
+                GameError::validation(path, format!("ステージ {} が重複しています", stage.id))
+                    .at_line(line),
+            );
+        }
+        if let Err(message) = validate_stage(stage, stages, monsters) {
+            problems.push(
+                GameError::validation(path, format!("{}: {}", stage.name, message)).at_line(line),
+            );
+        }
+    }
+
+    problems
+}
+
+// 群れの組み方とボス、解放条件の参照先を確認する
+fn validate_stage(
+    stage: &Stage,
+    stages: &[Stage],
+    monsters: &[Monster],
+) -> std::result::Result<(), String> {
+    validate_encounter(&stage.encounter)?;
+    match monsters.iter().find(|m| m.name == stage.boss) {
+        None => {
+            return Err(format!(
+                "ボス {} が {} に見つかりません",
+                stage.boss, MONSTERS_PATH
+            ));
+        }
+        // is_boss でないと逃げられるボス戦になってしまう
+        Some(boss) if !boss.is_boss => {
+            return Err(format!(
+                "ボス {} が {} で is_boss: true になっていません",
+                boss.name, MONSTERS_PATH
+            ));
+        }
+        Some(_) => {}
+    }
+    if let Some(id) = stage
+        .unlock
+        .cleared
+        .iter()
+        .find(|id| !stages.iter().any(|s| s.id == **id))
+    {
+        return Err(format!("unlock.cleared のステージ {} が見つかりません", id));
+    }
+
+    Ok(())
+}
+
+// 群れの組み方を確認する（config.yaml の encounter でも使う）
+pub(crate) fn validate_encounter(rule: &EncounterRule) -> std::result::Result<(), String> {
+    if rule.min_enemies == 0 || rule.min_enemies > rule.max_enemies {
+        return Err(format!(
+            "encounter の min_enemies ({}) は1以上 max_enemies ({}) 以下である必要があります",
+            rule.min_enemies, rule.max_enemies
+        ));
+    }
+    if rule.max_enemies > MAX_ENEMIES {
+        return Err(format!(
+            "encounter の max_enemies ({}) は {} 以下である必要があります",
+            rule.max_enemies, MAX_ENEMIES
+        ));
+    }
+    if rule.max_same == 0 {
+        return Err("encounter の max_same は1以上である必要があります".to_string());
+    }
+
+    Ok(())
+}
+
+// id からステージを探す
+pub fn find_stage(stages: &[Stage], id: u32) -> Option<&Stage> {
+    stages.iter().find(|stage| stage.id == id)
+}
+
+// ステージが解放されているかをチェックする
+pub fn is_stage_unlocked(stage: &Stage, character: &Character) -> bool {
+    character.hero.lv >= stage.unlock.level
+        && stage
+            .unlock
+            .cleared
+            .iter()
+            .all(|id| character.stages_cleared.contains(id))
+}
@@ -0,0 +1,174 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{battle::BattleEvent, GameRng};
+
+// デフォルトの付与確率（互換性のため）
+fn default_chance() -> f64 {
+    1.0
+}
+
+// 状態異常の種類
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusKind {
+    Poison,    // 毒（毎ターン最大HPの1/10のダメージ）
+    Sleep,     // 眠り（行動できない）
+    Paralysis, // 麻痺（半分の確率で行動できない）
+    Burn,      // やけど（毎ターン最大HPの1/8のダメージ）
+    Stun,      // 気絶（行動できない）
+}
+
+impl StatusKind {
+    // 状態異常の日本語名を返す
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "毒",
+            StatusKind::Sleep => "眠り",
+            StatusKind::Paralysis => "麻痺",
+            StatusKind::Burn => "やけど",
+            StatusKind::Stun => "気絶",
+        }
+    }
+
+    // 毎ターン受けるダメージ（最低1）、ダメージの無い状態異常は0
+    pub fn tick_damage(&self, max_hp: u32) -> u32 {
+        match self {
+            StatusKind::Poison => (max_hp / 10).max(1),
+            StatusKind::Burn => (max_hp / 8).max(1),
+            StatusKind::Sleep | StatusKind::Paralysis | StatusKind::Stun => 0,
+        }
+    }
+}
+
+// かかっている状態異常と残りターン数
+// キャラクターの状態異常はバトルが終わっても残り、セーブされる
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub turns: u32,
+}
+
+// 状態異常を与える攻撃（モンスターの攻撃やスキル）
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Inflict {
+    pub status: StatusKind,
+    pub turns: u32,
+    // かかる確率 (0.0-1.0)
+    #[serde(default = "default_chance")]
+    pub chance: f64,
+}
+
+impl Inflict {
+    // データファイルの値を確認する
+    pub(crate) fn validate(&self) -> std::result::Result<(), String> {
+        if self.turns == 0 {
+            return Err(format!(
+                "{}の turns は1以上である必要があります",
+                self.status.name()
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.chance) {
+            return Err(format!(
+                "{}の chance ({}) は 0.0 から 1.0 の範囲である必要があります",
+                self.status.name(),
+                self.chance
+            ));
+        }
+        Ok(())
+    }
+}
+
+// 状態異常の付与を判定する（すでにかかっていれば残りターンの長い方にする）
+pub(crate) fn inflict(
+    effects: &mut Vec<StatusEffect>,
+    inflict: &Inflict,
+    target: &str,
+    rng: &mut GameRng,
+) -> BattleEvent {
+    if !rng.gen_bool(inflict.chance) {
+        return BattleEvent::StatusMissed {
+            target: target.to_string(),
+            status: inflict.status,
+        };
+    }
+
+    match effects.iter_mut().find(|e| e.kind == inflict.status) {
+        Some(effect) => effect.turns = effect.turns.max(inflict.turns),
+        None => effects.push(StatusEffect {
+            kind: inflict.status,
+            turns: inflict.turns,
+        }),
+    }
+    BattleEvent::StatusInflicted {
+        target: target.to_string(),
+        status: inflict.status,
+        turns: inflict.turns,
+    }
+}
+
+// 行動の前に状態異常を1ターン進める
+// 毒・やけどのダメージを hp に与え、眠り・麻痺・気絶で行動できなければ true を返す
+pub(crate) fn start_turn(
+    effects: &mut Vec<StatusEffect>,
+    target: &str,
+    hp: &mut u32,
+    max_hp: u32,
+    rng: &mut GameRng,
+    events: &mut Vec<BattleEvent>,
+) -> bool {
+    let mut blocked = false;
+
+    for effect in effects.iter_mut() {
+        let damage = effect.kind.tick_damage(max_hp);
+        if damage > 0 {
+            *hp = hp.saturating_sub(damage);
+            events.push(BattleEvent::StatusDamage {
+                target: target.to_string(),
+                status: effect.kind,
+                damage,
+            });
+        }
+
+        let cannot_act = match effect.kind {
+            StatusKind::Sleep | StatusKind::Stun => true,
+            StatusKind::Paralysis => rng.gen_bool(0.5),
+            StatusKind::Poison | StatusKind::Burn => false,
+        };
+        if cannot_act && !blocked {
+            blocked = true;
+            events.push(BattleEvent::StatusBlocked {
+                target: target.to_string(),
+                status: effect.kind,
+            });
+        }
+
+        effect.turns -= 1;
+    }
+
+    effects.retain(|effect| {
+        if effect.turns == 0 {
+            events.push(BattleEvent::StatusRecovered {
+                target: target.to_string(),
+                status: effect.kind,
+            });
+        }
+        effect.turns > 0
+    });
+
+    blocked && *hp > 0
+}
+
+// 状態異常を治し、治った種類を返す（status が None ならすべて治す）
+pub fn cure(effects: &mut Vec<StatusEffect>, status: Option<StatusKind>) -> Vec<StatusKind> {
+    let mut cured = Vec::new();
+    effects.retain(|effect| {
+        if status.is_none_or(|kind| kind == effect.kind) {
+            cured.push(effect.kind);
+            false
+        } else {
+            true
+        }
+    });
+    cured
+}
@@ -0,0 +1,67 @@
+use std::{thread, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+// ゲームの進行速度
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GameSpeed {
+    #[default]
+    Normal, // 通常（従来どおりの待ち時間）
+    Fast,    // 高速（待ち時間を1/5にする）
+    Instant, // 待ち時間なし
+}
+
+impl GameSpeed {
+    // "normal" / "fast" / "instant" から GameSpeed を取得
+    pub fn from_name(name: &str) -> Option<GameSpeed> {
+        match name {
+            "normal" => Some(GameSpeed::Normal),
+            "fast" => Some(GameSpeed::Fast),
+            "instant" => Some(GameSpeed::Instant),
+            _ => None,
+        }
+    }
+
+    // 通常速度での待ち時間を、この速度での待ち時間に変換する
+    pub fn scale(&self, duration: Duration) -> Duration {
+        match self {
+            GameSpeed::Normal => duration,
+            GameSpeed::Fast => duration / 5,
+            GameSpeed::Instant => Duration::ZERO,
+        }
+    }
+}
+
+// 演出のための待ち時間を扱う
+// テストなどでは NoDelay に差し替えれば待たずに進む
+pub trait Pacer {
+    fn pause(&mut self, duration: Duration);
+}
+
+// 進行速度に合わせて実際にスリープする
+pub struct SpeedPacer {
+    speed: GameSpeed,
+}
+
+impl SpeedPacer {
+    pub fn new(speed: GameSpeed) -> Self {
+        SpeedPacer { speed }
+    }
+}
+
+impl Pacer for SpeedPacer {
+    fn pause(&mut self, duration: Duration) {
+        let duration = self.speed.scale(duration);
+        if !duration.is_zero() {
+            thread::sleep(duration);
+        }
+    }
+}
+
+// 一切待たない
+pub struct NoDelay;
+
+impl Pacer for NoDelay {
+    fn pause(&mut self, _duration: Duration) {}
+}
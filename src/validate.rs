@@ -0,0 +1,386 @@
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    ability::{Ability, AbilityEffect},
+    character::Character,
+    config::{check_config, Config, CONFIG_PATH},
+    equipment::{check_equipment, Equipment, EQUIPMENT_PATH},
+    error::{entry_line, find_key_line, read_data, GameError},
+    item::{check_items, Item, ITEMS_PATH},
+    monster::{
+        apply_encounter_weights, check_encounter_pool, check_monsters, Monster, MONSTERS_PATH,
+    },
+    party::{check_companions, CompanionDef, COMPANIONS_PATH},
+    shop::{check_shop, ShopEntry, SHOP_PATH},
+    skill::{check_skills, Skill, SKILLS_PATH},
+    stage::{check_stages, Stage, STAGES_PATH},
+};
+
+// データファイルを読み込み、解析できなければ問題に加えて None を返す
+fn read<T: DeserializeOwned>(file: &str, problems: &mut Vec<GameError>) -> Option<(String, T)> {
+    match read_data(file) {
+        Ok(read) => Some(read),
+        Err(e) => {
+            problems.push(e);
+            None
+        }
+    }
+}
+
+// すべてのデータファイルを検証し、見つかった問題をすべて返す（kt_rpg validate）
+// ファイルごとの確認に加えて、ファイルをまたぐ参照と、ゲーム中に出会えないデータも確認する
+// 解析できなかったファイルに関わる確認は行わない
+pub fn validate_content() -> Vec<GameError> {
+    let mut problems = Vec::new();
+
+    let monsters = read::<Vec<Monster>>(MONSTERS_PATH, &mut problems);
+    let items = read::<Vec<Item>>(ITEMS_PATH, &mut problems);
+    let equipment = read::<Vec<Equipment>>(EQUIPMENT_PATH, &mut problems);
+    let shop = read::<Vec<ShopEntry>>(SHOP_PATH, &mut problems);
+    let skills = read::<Vec<Skill>>(SKILLS_PATH, &mut problems);
+    let companions = read::<Vec<CompanionDef>>(COMPANIONS_PATH, &mut problems);
+    let stages = read::<Vec<Stage>>(STAGES_PATH, &mut problems);
+
+    if let Some((data, monsters)) = &monsters {
+        problems.extend(check_monsters(data, monsters));
+    }
+    if let Some((data, items)) = &items {
+        problems.extend(check_items(data, items));
+    }
+    if let Some((data, equipment)) = &equipment {
+        problems.extend(check_equipment(data, equipment));
+    }
+    if let Some((data, shop)) = &shop {
+        problems.extend(check_shop(data, shop));
+    }
+    if let Some((data, skills)) = &skills {
+        problems.extend(check_skills(data, skills));
+    }
+    if let Some((data, companions)) = &companions {
+        problems.extend(check_companions(data, companions));
+    }
+    if let (Some((data, stages)), Some((_, monsters))) = (&stages, &monsters) {
+        problems.extend(check_stages(data, stages, monsters));
+        problems.extend(check_stage_monsters(data, stages, monsters));
+    }
+
+    // アイテムID・装備IDの参照先と、手に入らないアイテム
+    if let (Some((item_data, items)), Some((equipment_data, equipment))) = (&items, &equipment) {
+        let ids: Vec<&str> = items
+            .iter()
+            .map(|item| item.id.as_str())
+            .chain(equipment.iter().map(|gear| gear.id.as_str()))
+            .collect();
+        if let Some((data, monsters)) = &monsters {
+            problems.extend(check_drop_items(data, monsters, &ids));
+        }
+        if let Some((data, shop)) = &shop {
+            problems.extend(check_shop_items(data, shop, &ids));
+        }
+        if let (Some((_, monsters)), Some((_, shop))) = (&monsters, &shop) {
+            problems.extend(check_obtainable(
+                (item_data, items),
+                (equipment_data, equipment),
+                monsters,
+                shop,
+            ));
+        }
+    }
+
+    // 仲間が仲間になるステージと、使えるスキル
+    if let Some((data, companions)) = &companions {
+        if let Some((_, stages)) = &stages {
+            problems.extend(check_join_stages(data, companions, stages));
+        }
+        if let Some((_, skills)) = &skills {
+            problems.extend(check_companion_skills(data, companions, skills));
+        }
+    }
+
+    // 出現しないモンスター
+    if let (Some((data, monsters)), Some((_, stages))) = (&monsters, &stages) {
+        problems.extend(check_encounters(data, monsters, stages));
+    }
+
+    // 設定ファイルは無くてもよい
+    let config = if Path::new(CONFIG_PATH).exists() {
+        read::<Config>(CONFIG_PATH, &mut problems)
+    } else {
+        Some((String::new(), Config::default()))
+    };
+
+    if let Some((data, config)) = &config {
+        problems.extend(check_config(data, config));
+    }
+
+    // config.yaml で上書きしたあと、通常モード・ブーストモードで出現するモンスターがいるか
+    if let (Some((data, config)), Some((_, monsters))) = (&config, &monsters) {
+        problems.extend(check_encounter_weights(data, config, monsters));
+
+        let mut monsters = monsters.clone();
+        apply_encounter_weights(&mut monsters, &config.encounter_weights);
+        if let Err(e) = check_encounter_pool(&monsters) {
+            problems.push(e);
+        }
+    }
+
+    problems
+}
+
+// ボス以外のモンスターがいて、前のステージから辿り着けるかを確認する
+fn check_stage_monsters(data: &str, stages: &[Stage], monsters: &[Monster]) -> Vec<GameError> {
+    let path = Path::new(STAGES_PATH);
+    let mut problems = Vec::new();
+
+    for (i, stage) in stages.iter().enumerate() {
+        if !monsters.iter().any(|m| m.stage == stage.id && !m.is_boss) {
+            problems.push(
+                GameError::validation(
+                    path,
+                    format!(
+                        "{}: このステージに出現するボス以外のモンスターが {} にいません",
+                        stage.name, MONSTERS_PATH
+                    ),
+                )
+                .at_line(entry_line(data, i)),
+            );
+        }
+    }
+
+    // 解放条件を満たせるステージを、条件の無いステージから順に広げていく
+    let mut reachable: Vec<u32> = Vec::new();
+    loop {
+        let next: Vec<u32> = stages
+            .iter()
+            .filter(|stage| !reachable.contains(&stage.id))
+            .filter(|stage| stage.unlock.cleared.iter().all(|id| reachable.contains(id)))
+            .map(|stage| stage.id)
+            .collect();
+        if next.is_empty() {
+            break;
+        }
+        reachable.extend(next);
+    }
+    for (i, stage) in stages
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| !reachable.contains(&s.id))
+    {
+        problems.push(
+            GameError::validation(
+                path,
+                format!(
+                    "{}: unlock.cleared の条件が循環していて、挑戦できません",
+                    stage.name
+                ),
+            )
+            .at_line(entry_line(data, i)),
+        );
+    }
+
+    problems
+}
+
+// ドロップするアイテムが items.yaml か equipment.yaml にあるかを確認する
+fn check_drop_items(data: &str, monsters: &[Monster], ids: &[&str]) -> Vec<GameError> {
+    let path = Path::new(MONSTERS_PATH);
+    let mut problems = Vec::new();
+
+    for (i, monster) in monsters.iter().enumerate() {
+        let dropped = monster
+            .drops
+            .iter()
+            .map(|d| &d.item)
+            .chain(monster.guaranteed_drops.iter().map(|d| &d.item));
+        for item in dropped.filter(|item| !ids.contains(&item.as_str())) {
+            problems.push(
+                GameError::validation(
+                    path,
+                    format!(
+                        "{}: 落とすアイテム {} が {} にも {} にもありません",
+                        monster.name, item, ITEMS_PATH, EQUIPMENT_PATH
+                    ),
+                )
+                .at_line(entry_line(data, i)),
+            );
+        }
+    }
+
+    problems
+}
+
+// 商品が items.yaml か equipment.yaml にあるかを確認する
+fn check_shop_items(data: &str, shop: &[ShopEntry], ids: &[&str]) -> Vec<GameError> {
+    let path = Path::new(SHOP_PATH);
+
+    shop.iter()
+        .enumerate()
+        .filter(|(_, entry)| !ids.contains(&entry.item.as_str()))
+        .map(|(i, entry)| {
+            GameError::validation(
+                path,
+                format!(
+                    "商品 {} が {} にも {} にもありません",
+                    entry.item, ITEMS_PATH, EQUIPMENT_PATH
+                ),
+            )
+            .at_line(entry_line(data, i))
+        })
+        .collect()
+}
+
+// 最初から持っている、ショップで買える、モンスターが落とす、のどれでもないアイテムを探す
+fn check_obtainable(
+    (item_data, items): (&str, &[Item]),
+    (equipment_data, equipment): (&str, &[Equipment]),
+    monsters: &[Monster],
+    shop: &[ShopEntry],
+) -> Vec<GameError> {
+    let starting = Character::new(String::new()).inventory;
+    let obtainable = |id: &str| {
+        starting.count(id) > 0
+            || shop.iter().any(|entry| entry.item == id)
+            || monsters.iter().any(|m| {
+                m.drops.iter().any(|d| d.item == id)
+                    || m.guaranteed_drops.iter().any(|d| d.item == id)
+            })
+    };
+    let message = |id: &str| format!("{} はショップでもドロップでも手に入りません", id);
+
+    let mut problems = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        if !obtainable(&item.id) {
+            problems.push(
+                GameError::validation(ITEMS_PATH, message(&item.id))
+                    .at_line(entry_line(item_data, i)),
+            );
+        }
+    }
+    for (i, gear) in equipment.iter().enumerate() {
+        if !obtainable(&gear.id) {
+            problems.push(
+                GameError::validation(EQUIPMENT_PATH, message(&gear.id))
+                    .at_line(entry_line(equipment_data, i)),
+            );
+        }
+    }
+
+    problems
+}
+
+// 仲間になるステージが stages.yaml にあるかを確認する
+fn check_join_stages(data: &str, companions: &[CompanionDef], stages: &[Stage]) -> Vec<GameError> {
+    let path = Path::new(COMPANIONS_PATH);
+
+    companions
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| !stages.iter().any(|s| s.id == c.join_stage))
+        .map(|(i, c)| {
+            GameError::validation(
+                path,
+                format!(
+                    "{}: join_stage のステージ {} が {} に無いので、仲間になりません",
+                    c.name, c.join_stage, STAGES_PATH
+                ),
+            )
+            .at_line(entry_line(data, i))
+        })
+        .collect()
+}
+
+// 仲間のスキルが skills.yaml にあるかを確認する
+fn check_companion_skills(
+    data: &str,
+    companions: &[CompanionDef],
+    skills: &[Skill],
+) -> Vec<GameError> {
+    let path = Path::new(COMPANIONS_PATH);
+    let mut problems = Vec::new();
+
+    for (i, companion) in companions.iter().enumerate() {
+        for id in companion
+            .skills
+            .iter()
+            .filter(|id| !skills.iter().any(|s| &s.id == *id))
+        {
+            problems.push(
+                GameError::validation(
+                    path,
+                    format!(
+                        "{}: スキル {} が {} にありません",
+                        companion.name, id, SKILLS_PATH
+                    ),
+                )
+                .at_line(entry_line(data, i)),
+            );
+        }
+    }
+
+    problems
+}
+
+// 通常モード・ステージ・ボス・召喚のどれでも出現しないモンスターを探す
+fn check_encounters(data: &str, monsters: &[Monster], stages: &[Stage]) -> Vec<GameError> {
+    let path = Path::new(MONSTERS_PATH);
+    let summons = |abilities: &[Ability], name: &str| {
+        abilities
+            .iter()
+            .any(|a| matches!(&a.effect, AbilityEffect::Summon { monster } if monster == name))
+    };
+    let summoned = |name: &str| {
+        monsters.iter().any(|m| {
+            summons(&m.abilities, name)
+                || m.phases
+                    .iter()
+                    .any(|p| p.abilities.as_deref().is_some_and(|a| summons(a, name)))
+        })
+    };
+
+    // ボス以外は所属ステージの通常バトルに出る
+    let appears = |m: &Monster| {
+        m.encounter_weight > 0
+            || stages.iter().any(|s| s.boss == m.name)
+            || (!m.is_boss && stages.iter().any(|s| s.id == m.stage))
+            || summoned(&m.name)
+    };
+
+    monsters
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| !appears(m))
+        .map(|(i, m)| {
+            GameError::validation(
+                path,
+                format!(
+                    "{}: encounter_weight が0で、どのステージにもボスにも召喚にも使われていないので出現しません",
+                    m.name
+                ),
+            )
+            .at_line(entry_line(data, i))
+        })
+        .collect()
+}
+
+// config.yaml の出現しやすさの表にあるモンスター名を確認する
+fn check_encounter_weights(data: &str, config: &Config, monsters: &[Monster]) -> Vec<GameError> {
+    let path = Path::new(CONFIG_PATH);
+
+    config
+        .encounter_weights
+        .keys()
+        .filter(|name| !monsters.iter().any(|m| &&m.name == name))
+        .map(|name| {
+            GameError::validation(
+                path,
+                format!(
+                    "encounter_weights のモンスター {} が {} にありません",
+                    name, MONSTERS_PATH
+                ),
+            )
+            .at_line(find_key_line(data, name))
+        })
+        .collect()
+}
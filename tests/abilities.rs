@@ -0,0 +1,158 @@
+// モンスターの特技と行動パターンを確かめる
+
+mod common;
+
+use common::{empty_content, hero, monsters};
+use kt_rpg::{
+    battle::{Action, Battle, BattleEvent},
+    content::Content,
+    GameRng,
+};
+use rand::SeedableRng;
+
+// 倒されないよう HP を多くした、攻撃力1のモンスターに特技をつける
+fn caster(hp: u32, abilities: &str, behavior: &str) -> Battle {
+    Battle::new(monsters(&format!(
+        "
+- name: 魔術師
+  hp: {}
+  max_hp: 1000
+  min_attack: 1
+  max_attack: 1
+  exp: 1
+  speed: 1
+  abilities: {}
+  behavior: {}
+",
+        hp, abilities, behavior
+    )))
+}
+
+fn turn(battle: &mut Battle, content: &Content, rng: &mut GameRng) -> Vec<BattleEvent> {
+    let mut character = hero();
+    battle.turn(&mut character, vec![Action::Defend], content, rng)
+}
+
+#[test]
+fn heal_is_used_only_below_the_hp_threshold() {
+    let abilities = "[{ id: heal, name: 回復, effect: { type: heal, percent: 30 } }]";
+    let behavior = "[{ ability: heal, hp_below: 50 }]";
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let mut battle = caster(600, abilities, behavior);
+    let events = turn(&mut battle, &empty_content(), &mut rng);
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, BattleEvent::MonsterHealed { .. })));
+
+    let mut battle = caster(400, abilities, behavior);
+    let events = turn(&mut battle, &empty_content(), &mut rng);
+    assert!(events.contains(&BattleEvent::MonsterHealed {
+        monster: "魔術師".to_string(),
+        amount: 300,
+    }));
+    assert_eq!(battle.enemies()[0].hp(), 700);
+}
+
+#[test]
+fn charged_strike_waits_before_hitting() {
+    let abilities =
+        "[{ id: meteor, name: メテオ, effect: { type: strike, power: 1000, charge_turns: 2 } }]";
+    let mut battle = caster(1000, abilities, "[{ ability: meteor, once: true }]");
+    let mut rng = GameRng::seed_from_u64(1);
+    let charging = BattleEvent::Charging {
+        monster: "魔術師".to_string(),
+        ability: "メテオ".to_string(),
+    };
+
+    assert!(turn(&mut battle, &empty_content(), &mut rng).contains(&charging));
+    assert!(turn(&mut battle, &empty_content(), &mut rng).contains(&charging));
+
+    // ため終わると威力10倍で放つ（防御しているので半分）
+    let events = turn(&mut battle, &empty_content(), &mut rng);
+    assert!(events.contains(&BattleEvent::MonsterAbility {
+        monster: "魔術師".to_string(),
+        ability: "メテオ".to_string(),
+    }));
+    assert!(events.contains(&BattleEvent::DamageTaken {
+        monster: "魔術師".to_string(),
+        target: "勇者".to_string(),
+        damage: 5,
+    }));
+}
+
+#[test]
+fn enrage_once_raises_attack_for_the_rest_of_the_battle() {
+    let abilities = "[{ id: rage, name: 怒り, effect: { type: enrage, attack: 7 } }]";
+    let mut battle = caster(1000, abilities, "[{ ability: rage, once: true }]");
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let events = turn(&mut battle, &empty_content(), &mut rng);
+    assert!(events.contains(&BattleEvent::Enraged {
+        monster: "魔術師".to_string(),
+        attack: 7,
+    }));
+
+    // 2回目は使わず、上がった攻撃力で通常攻撃する（防御しているので半分）
+    let events = turn(&mut battle, &empty_content(), &mut rng);
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, BattleEvent::Enraged { .. })));
+    assert!(events.contains(&BattleEvent::DamageTaken {
+        monster: "魔術師".to_string(),
+        target: "勇者".to_string(),
+        damage: 4,
+    }));
+}
+
+#[test]
+fn summon_adds_the_minion_to_the_battle() {
+    let abilities = "[{ id: call, name: 仲間を呼ぶ, effect: { type: summon, monster: ゴブリン } }]";
+    let mut battle = caster(1000, abilities, "[{ ability: call }]");
+    let mut content = empty_content();
+    content.monsters = monsters(
+        "
+- name: ゴブリン
+  hp: 20
+  max_hp: 20
+  min_attack: 1
+  max_attack: 1
+  exp: 1
+",
+    );
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let events = turn(&mut battle, &content, &mut rng);
+    assert!(events.contains(&BattleEvent::Summoned {
+        monster: "魔術師".to_string(),
+        minion: "ゴブリン".to_string(),
+    }));
+    assert!(events.contains(&BattleEvent::Encounter {
+        name: "ゴブリン".to_string(),
+        hp: 20,
+    }));
+    assert_eq!(battle.living_enemies(), vec![0, 1]);
+
+    // 2体目は B がつく
+    let events = turn(&mut battle, &content, &mut rng);
+    assert!(events.contains(&BattleEvent::Summoned {
+        monster: "魔術師".to_string(),
+        minion: "ゴブリンB".to_string(),
+    }));
+}
+
+#[test]
+fn summon_without_known_minion_falls_back_to_attack() {
+    let abilities = "[{ id: call, name: 仲間を呼ぶ, effect: { type: summon, monster: ゴブリン } }]";
+    let mut battle = caster(1000, abilities, "[{ ability: call }]");
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let events = turn(&mut battle, &empty_content(), &mut rng);
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, BattleEvent::MonsterAbility { .. })));
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, BattleEvent::DamageTaken { .. })));
+    assert_eq!(battle.enemies().len(), 1);
+}
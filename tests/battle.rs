@@ -0,0 +1,147 @@
+// シードを固定したバトルが、毎回まったく同じ出来事になることを確かめる
+// 表示や入力は使わず、エンジンだけを動かす（待ち時間は NoDelay で飛ばす）
+
+mod common;
+
+use common::{empty_content, hero};
+use kt_rpg::{
+    battle::{Action, Battle, BattleEvent, BattleResult},
+    character::{Character, LevelUp},
+    monster::Monster,
+    timing::{NoDelay, Pacer},
+    GameRng,
+};
+use rand::SeedableRng;
+use std::time::Duration;
+
+// スライム1体（ドロップ無し）
+fn slime(exp: u32) -> Vec<Monster> {
+    common::monsters(&format!(
+        "
+- name: スライム
+  hp: 30
+  max_hp: 30
+  min_attack: 1
+  max_attack: 3
+  exp: {}
+  speed: 4
+  min_gold: 3
+  max_gold: 6
+  drop_chance: 0.0
+",
+        exp
+    ))
+}
+
+// フロントエンドと同じ順番で、決着がつくまで毎ターン攻撃する
+// ターンごとの出来事と勝敗を返す
+fn run_battle(
+    character: &mut Character,
+    monsters: Vec<Monster>,
+    seed: u64,
+    pacer: &mut dyn Pacer,
+) -> (Vec<Vec<BattleEvent>>, BattleResult) {
+    let content = empty_content();
+    let mut rng = GameRng::seed_from_u64(seed);
+    let mut battle = Battle::new(monsters);
+    let mut turns = vec![battle.encounter()];
+
+    loop {
+        pacer.pause(Duration::from_secs(1));
+        turns.push(battle.turn(character, vec![Action::Attack(0)], &content, &mut rng));
+        if let Some(result) = battle.result() {
+            return (turns, result);
+        }
+        pacer.pause(Duration::from_secs(3));
+    }
+}
+
+#[test]
+fn seeded_turn_produces_exact_events() {
+    let mut character = hero();
+    let mut battle = Battle::new(slime(10));
+    let mut rng = GameRng::seed_from_u64(42);
+
+    let events = battle.turn(
+        &mut character,
+        vec![Action::Attack(0)],
+        &empty_content(),
+        &mut rng,
+    );
+
+    // 素早さは勇者 5、スライム 4 なので勇者が先に動く
+    assert_eq!(
+        events,
+        vec![
+            BattleEvent::Attack {
+                actor: "勇者".to_string(),
+                target: "スライム".to_string(),
+                damage: 5,
+            },
+            BattleEvent::DamageTaken {
+                monster: "スライム".to_string(),
+                target: "勇者".to_string(),
+                damage: 2,
+            },
+        ]
+    );
+    assert_eq!(battle.enemies()[0].hp(), 25);
+    assert_eq!(character.hero.hp, 48);
+    assert_eq!(battle.result(), None);
+}
+
+#[test]
+fn seeded_battle_ends_the_same_way() {
+    let mut character = hero();
+    let (turns, result) = run_battle(&mut character, slime(10), 42, &mut NoDelay);
+
+    assert_eq!(result, BattleResult::Win);
+    // 遭遇 + 8ターン
+    assert_eq!(turns.len(), 9);
+    assert_eq!(
+        turns.last().unwrap(),
+        &vec![
+            BattleEvent::Attack {
+                actor: "勇者".to_string(),
+                target: "スライム".to_string(),
+                damage: 4,
+            },
+            BattleEvent::MonsterDefeated {
+                name: "スライム".to_string(),
+            },
+            BattleEvent::ExpGained { exp: 10 },
+            BattleEvent::GoldGained { gold: 4 },
+        ]
+    );
+    // 勝利すると全回復する
+    assert_eq!(character.hero.hp, 50);
+    assert_eq!(character.hero.exp, 10);
+    assert_eq!(character.gold, 4);
+
+    // 同じシードならもう一度戦っても同じ展開になる
+    let mut again = hero();
+    assert_eq!(run_battle(&mut again, slime(10), 42, &mut NoDelay).0, turns);
+}
+
+#[test]
+fn seeded_level_up_rolls_exact_increases() {
+    let mut character = hero();
+    let (turns, _) = run_battle(&mut character, slime(20), 42, &mut NoDelay);
+
+    assert_eq!(
+        turns.last().unwrap().last(),
+        Some(&BattleEvent::LevelUp(LevelUp {
+            name: "勇者".to_string(),
+            lv: 2,
+            hp_increase: 6,
+            attack_increase: 3,
+            recovery_increase: 1,
+            mp_increase: 3,
+            speed_increase: 0,
+            learned: Vec::new(),
+        }))
+    );
+    assert_eq!(character.hero.lv, 2);
+    assert_eq!(character.hero.max_hp, 56);
+    assert_eq!(character.hero.stats.min_attack, 5);
+}
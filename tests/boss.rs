@@ -0,0 +1,102 @@
+// ボスの形態変化を確かめる
+
+mod common;
+
+use common::{empty_content, hero, monsters};
+use kt_rpg::{
+    battle::{Action, Battle, BattleEvent, BattleResult},
+    GameRng,
+};
+use rand::SeedableRng;
+
+// 1撃で倒れる2つの形態を持ったボス
+fn demon_lord() -> Battle {
+    Battle::new(monsters(
+        "
+- name: 魔王
+  hp: 1
+  max_hp: 1
+  min_attack: 1
+  max_attack: 1
+  exp: 100
+  speed: 1
+  is_boss: true
+  phases:
+    - name: 真の魔王
+      hp: 1
+      min_attack: 30
+      max_attack: 30
+      dialogue: [\"まだ終わらんぞ！\"]
+",
+    ))
+}
+
+#[test]
+fn boss_transforms_instead_of_being_defeated() {
+    let mut character = hero();
+    let mut battle = demon_lord();
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let events = battle.turn(
+        &mut character,
+        vec![Action::Attack(0)],
+        &empty_content(),
+        &mut rng,
+    );
+
+    assert!(events.contains(&BattleEvent::PhaseChanged {
+        monster: "魔王".to_string(),
+        name: "真の魔王".to_string(),
+        hp: 1,
+        dialogue: vec!["まだ終わらんぞ！".to_string()],
+    }));
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, BattleEvent::MonsterDefeated { .. })));
+    assert_eq!(character.hero.exp, 0);
+    assert_eq!(battle.result(), None);
+    // 変身した形態の攻撃力で殴ってくる
+    assert!(events.contains(&BattleEvent::DamageTaken {
+        monster: "真の魔王".to_string(),
+        target: "勇者".to_string(),
+        damage: 30,
+    }));
+
+    let events = battle.turn(
+        &mut character,
+        vec![Action::Attack(0)],
+        &empty_content(),
+        &mut rng,
+    );
+    assert!(events.contains(&BattleEvent::MonsterDefeated {
+        name: "真の魔王".to_string(),
+    }));
+    assert!(events.contains(&BattleEvent::ExpGained { exp: 100 }));
+    assert_eq!(battle.result(), Some(BattleResult::Win));
+}
+
+#[test]
+fn losing_to_a_later_phase_is_a_defeat() {
+    let mut character = hero();
+    character.hero.hp = 20;
+    let mut battle = demon_lord();
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let events = battle.turn(
+        &mut character,
+        vec![Action::Attack(0)],
+        &empty_content(),
+        &mut rng,
+    );
+
+    assert!(events.contains(&BattleEvent::Defeat));
+    assert_eq!(battle.result(), Some(BattleResult::Lose));
+}
+
+#[test]
+fn boss_battle_cannot_be_fled() {
+    let battle = demon_lord();
+
+    assert!(!battle.can_flee());
+    assert_eq!(battle.flee_chance(&hero()), 0.0);
+}
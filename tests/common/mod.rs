@@ -0,0 +1,28 @@
+// 結合テストで共通に使うデータ
+// テストファイルごとに使う関数が違うので、使われない関数の警告は出さない
+#![allow(dead_code)]
+
+use kt_rpg::{character::Character, content::Content, monster::Monster};
+
+// バトルで参照するデータは使わないので空にしておく
+pub fn empty_content() -> Content {
+    Content {
+        monsters: Vec::new(),
+        items: Vec::new(),
+        equipment: Vec::new(),
+        shop: Vec::new(),
+        skills: Vec::new(),
+        companions: Vec::new(),
+        stages: Vec::new(),
+    }
+}
+
+// monsters.yaml と同じ形式で書いたモンスターの一覧
+pub fn monsters(yaml: &str) -> Vec<Monster> {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+// Lv1の主人公
+pub fn hero() -> Character {
+    Character::new("勇者".to_string())
+}
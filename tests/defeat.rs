@@ -0,0 +1,92 @@
+// 全滅したときのペナルティと復活を確かめる
+
+mod common;
+
+use common::hero;
+use kt_rpg::{
+    battle::BattleEvent,
+    character::Character,
+    defeat::{apply_defeat, DefeatRules, Hardcore, Respawn},
+    status::{StatusEffect, StatusKind},
+};
+
+// 全滅した直後の、ゴールドと経験値を持ったキャラクター
+fn defeated(rules: DefeatRules) -> Character {
+    let mut character = hero();
+    character.defeat_rules = rules;
+    character.gold = 101;
+    character.hero.exp = 40;
+    character.hero.hp = 0;
+    character.hero.mp = 0;
+    character.hero.status = vec![StatusEffect {
+        kind: StatusKind::Poison,
+        turns: 2,
+    }];
+    character
+}
+
+#[test]
+fn penalties_are_taken_and_the_party_revives() {
+    let mut character = defeated(DefeatRules {
+        gold_loss: 50,
+        exp_loss: 25,
+        respawn: Respawn::Town,
+        hardcore: None,
+    });
+
+    assert_eq!(
+        apply_defeat(&mut character),
+        vec![
+            BattleEvent::GoldLost { gold: 50 },
+            BattleEvent::ExpLost {
+                name: "勇者".to_string(),
+                exp: 10,
+            },
+            BattleEvent::Revived {
+                respawn: Respawn::Town,
+            },
+        ]
+    );
+    assert_eq!(character.gold, 51);
+    assert_eq!(character.hero.exp, 30);
+    assert_eq!(character.hero.hp, character.hero.max_hp);
+    assert_eq!(character.hero.mp, character.hero.max_mp);
+    assert!(character.hero.status.is_empty());
+    assert!(!character.game_over);
+}
+
+#[test]
+fn default_rules_only_send_the_party_back_to_town() {
+    let mut character = defeated(DefeatRules::default());
+
+    assert_eq!(
+        apply_defeat(&mut character),
+        vec![BattleEvent::Revived {
+            respawn: Respawn::Town,
+        }]
+    );
+    assert_eq!(character.gold, 101);
+    assert_eq!(character.hero.exp, 40);
+}
+
+#[test]
+fn hardcore_defeat_is_game_over_without_revival() {
+    let mut character = defeated(DefeatRules {
+        gold_loss: 50,
+        exp_loss: 0,
+        respawn: Respawn::Spot,
+        hardcore: Some(Hardcore::Lock),
+    });
+
+    assert_eq!(
+        apply_defeat(&mut character),
+        vec![BattleEvent::GameOver {
+            hardcore: Hardcore::Lock,
+        }]
+    );
+    assert!(character.game_over);
+    assert_eq!(character.gold, 101);
+    assert_eq!(character.hero.hp, 0);
+    // ハードコアではその場で起き上がれない
+    assert!(!character.defeat_rules.revives_on_spot());
+}
@@ -0,0 +1,62 @@
+// 通常モードで現れるモンスターの群れの組み方を確かめる
+
+mod common;
+
+use common::monsters;
+use kt_rpg::{
+    config::Config,
+    monster::{choose_monsters, Monster},
+    GameRng,
+};
+use rand::SeedableRng;
+
+// 通常モードで出現する2種類のモンスター
+fn pool() -> Vec<Monster> {
+    monsters(
+        "
+- name: スライム
+  hp: 10
+  max_hp: 10
+  min_attack: 1
+  max_attack: 2
+  exp: 1
+  encounter_weight: 1
+- name: ゴブリン
+  hp: 10
+  max_hp: 10
+  min_attack: 1
+  max_attack: 2
+  exp: 1
+  encounter_weight: 1
+",
+    )
+}
+
+#[test]
+fn normal_mode_spawns_one_enemy_by_default() {
+    let config: Config = serde_yaml::from_str("speed: fast").unwrap();
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let groups = choose_monsters(&pool(), config.encounter, &mut rng).unwrap();
+
+    assert_eq!(groups.len(), 10);
+    assert!(groups.iter().all(|group| group.len() == 1));
+}
+
+#[test]
+fn config_can_ask_for_groups() {
+    let config: Config = serde_yaml::from_str(
+        "
+encounter:
+  min_enemies: 2
+  max_enemies: 3
+  max_same: 2
+",
+    )
+    .unwrap();
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let groups = choose_monsters(&pool(), config.encounter, &mut rng).unwrap();
+
+    assert!(groups.iter().all(|group| (2..=3).contains(&group.len())));
+}
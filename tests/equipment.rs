@@ -0,0 +1,92 @@
+// 装備による能力値の上昇と属性を確かめる
+
+use kt_rpg::{
+    character::Character,
+    element::Element,
+    equipment::{equip, equipment_bonus, unequip, EquipSlot, Equipment},
+    party::CompanionDef,
+};
+
+// 炎の剣と、（データの検証を通らない）氷の属性を持った鎧
+fn gear() -> Vec<Equipment> {
+    serde_yaml::from_str(
+        "
+- id: flame_sword
+  name: 炎の剣
+  slot: weapon
+  attack: 9
+  element: fire
+- id: frost_mail
+  name: 氷の鎧
+  slot: armor
+  defense: 4
+  element: ice
+",
+    )
+    .unwrap()
+}
+
+fn equipped(ids: &[&str]) -> Character {
+    let mut character = Character::new("勇者".to_string());
+    let gear = gear();
+    for id in ids {
+        let piece = gear.iter().find(|g| g.id == *id).unwrap();
+        character.inventory.add(id, 1);
+        equip(&mut character, 0, piece).unwrap();
+    }
+    character
+}
+
+#[test]
+fn attack_element_comes_from_the_weapon() {
+    let character = equipped(&["frost_mail", "flame_sword"]);
+    let bonus = equipment_bonus(&character.hero, &gear());
+
+    assert_eq!(bonus.attack, 9);
+    assert_eq!(bonus.defense, 4);
+    assert_eq!(bonus.element, Some(Element::Fire));
+}
+
+#[test]
+fn armor_element_does_not_change_the_attack_element() {
+    let character = equipped(&["frost_mail"]);
+    let bonus = equipment_bonus(&character.hero, &gear());
+
+    assert_eq!(bonus.defense, 4);
+    assert_eq!(bonus.element, None);
+}
+
+#[test]
+fn companion_equipment_is_kept_apart_from_the_hero() {
+    let mut character = equipped(&["flame_sword"]);
+    let companion: CompanionDef = serde_yaml::from_str(
+        "
+name: 戦士
+join_stage: 1
+lv: 1
+hp: 60
+mp: 0
+stats:
+  min_attack: 4
+  max_attack: 7
+  min_recovery: 1
+  max_recovery: 2
+  speed: 3
+",
+    )
+    .unwrap();
+    character.companions.push(companion.to_member());
+
+    // 主人公の剣を外して仲間に持たせる（所持品はパーティで共有）
+    assert_eq!(
+        unequip(&mut character, 0, EquipSlot::Weapon),
+        Some("flame_sword".to_string())
+    );
+    equip(&mut character, 1, &gear()[0]).unwrap();
+
+    assert_eq!(equipment_bonus(&character.hero, &gear()).attack, 0);
+    let bonus = equipment_bonus(&character.companions[0], &gear());
+    assert_eq!(bonus.attack, 9);
+    assert_eq!(bonus.element, Some(Element::Fire));
+    assert_eq!(character.inventory.count("flame_sword"), 0);
+}
@@ -0,0 +1,92 @@
+// 逃走の成功率と、逃げたときのバトルの結果を確かめる
+
+mod common;
+
+use common::{empty_content, hero, monsters};
+use kt_rpg::{
+    battle::{Action, Battle, BattleEvent, BattleResult},
+    GameRng,
+};
+use rand::SeedableRng;
+
+// レベルと素早さ、ボスかどうかを指定したゴブリン
+fn goblin(lv: u32, speed: u32, is_boss: bool) -> Battle {
+    Battle::new(monsters(&format!(
+        "
+- name: ゴブリン
+  hp: 100
+  max_hp: 100
+  min_attack: 1
+  max_attack: 1
+  exp: 50
+  lv: {}
+  speed: {}
+  is_boss: {}
+",
+        lv, speed, is_boss
+    )))
+}
+
+// 確率を誤差を許して比べる
+fn assert_near(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn flee_chance_depends_on_level_and_speed() {
+    // 勇者は Lv1、素早さ 5
+    let character = hero();
+
+    assert_near(goblin(1, 5, false).flee_chance(&character), 0.5);
+    // レベル差 -2 で -10%、素早さの差 -5 で -10%
+    assert_near(goblin(3, 10, false).flee_chance(&character), 0.3);
+    assert_near(goblin(1, 0, false).flee_chance(&character), 0.6);
+    // 5%～95% に収まる
+    assert_near(goblin(30, 5, false).flee_chance(&character), 0.05);
+    let mut strong = hero();
+    strong.hero.lv = 30;
+    assert_near(goblin(1, 5, false).flee_chance(&strong), 0.95);
+}
+
+#[test]
+fn escaping_ends_the_battle_without_exp() {
+    let mut character = hero();
+    character.hero.lv = 30;
+    let mut battle = goblin(1, 1, false);
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let events = battle.turn(
+        &mut character,
+        vec![Action::Flee],
+        &empty_content(),
+        &mut rng,
+    );
+
+    // 逃げたあとはモンスターも行動しない
+    assert_eq!(events, vec![BattleEvent::FleeSucceeded]);
+    assert_eq!(battle.result(), Some(BattleResult::Escaped));
+    assert_eq!(character.hero.exp, 0);
+}
+
+#[test]
+fn fleeing_a_boss_does_not_use_the_turn() {
+    let mut character = hero();
+    let mut battle = goblin(1, 1, true);
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let events = battle.turn(
+        &mut character,
+        vec![Action::Flee],
+        &empty_content(),
+        &mut rng,
+    );
+
+    assert_eq!(events, vec![BattleEvent::CannotFlee]);
+    assert_eq!(battle.result(), None);
+    assert_eq!(character.hero.hp, character.hero.max_hp);
+}
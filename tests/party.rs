@@ -0,0 +1,79 @@
+// パーティでのバトルの報酬を確かめる
+
+mod common;
+
+use common::{empty_content, hero, monsters};
+use kt_rpg::{
+    battle::{Action, Battle, BattleEvent},
+    party::CompanionDef,
+    GameRng,
+};
+use rand::SeedableRng;
+
+#[test]
+fn downed_companion_gets_no_exp_and_stays_down() {
+    let mut character = hero();
+    let companion: CompanionDef = serde_yaml::from_str(
+        "
+name: 戦士
+join_stage: 1
+lv: 1
+hp: 60
+mp: 0
+stats:
+  min_attack: 4
+  max_attack: 7
+  min_recovery: 1
+  max_recovery: 2
+  speed: 3
+",
+    )
+    .unwrap();
+    let mut companion = companion.to_member();
+    companion.hp = 0;
+    character.companions.push(companion);
+
+    // 1体倒してもバトルが続くよう2体出す
+    let mut battle = Battle::new(monsters(
+        "
+- name: スライム
+  hp: 1
+  max_hp: 1
+  min_attack: 1
+  max_attack: 1
+  exp: 100
+  speed: 1
+  drop_chance: 0.0
+- name: スライム
+  hp: 100
+  max_hp: 100
+  min_attack: 1
+  max_attack: 1
+  exp: 100
+  speed: 1
+  drop_chance: 0.0
+",
+    ));
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let events = battle.turn(
+        &mut character,
+        vec![Action::Attack(0)],
+        &empty_content(),
+        &mut rng,
+    );
+
+    assert!(events.contains(&BattleEvent::MonsterDefeated {
+        name: "スライムA".to_string(),
+    }));
+    assert_eq!(battle.result(), None);
+    // 生きている勇者だけがレベルアップする
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, BattleEvent::LevelUp(level_up) if level_up.name == "勇者")));
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, BattleEvent::LevelUp(level_up) if level_up.name == "戦士")));
+    let companion = &character.companions[0];
+    assert_eq!((companion.lv, companion.exp, companion.hp), (1, 0, 0));
+}
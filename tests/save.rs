@@ -0,0 +1,308 @@
+// セーブの読み書きを実際のファイルで確かめる
+// セーブは作業ディレクトリの saves/ に書かれるので、一時ディレクトリに移ってから動かす
+
+use std::{env, fs, process, sync::OnceLock};
+
+use kt_rpg::{
+    character::Character,
+    party::CompanionDef,
+    save::{
+        backup_path, corrupt_path, delete_slot, load_character, migrate_save,
+        migration_backup_path, restore_backup, save_character, slot_path, validate_slot_name,
+        SAVES_DIR, SAVE_BACKUPS, SAVE_VERSION,
+    },
+};
+
+// 作業ディレクトリはプロセス全体で共有されるので、移るのは一度だけにする
+// テストは並んで動くため、テストごとに別のスロット名を使う
+fn enter_temp_dir() {
+    static DIR: OnceLock<()> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = env::temp_dir().join(format!("kt_rpg_save_test_{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join(SAVES_DIR)).unwrap();
+        env::set_current_dir(&dir).unwrap();
+    });
+}
+
+// version の無い、ステージ導入前のセーブ（リポジトリの savefile.yaml と同じ形式）
+const VERSIONLESS_SAVE: &str = "\
+name: test
+lv: 1
+hp: 50
+max_hp: 50
+stats:
+  min_attack: 2
+  max_attack: 5
+  min_recovery: 1
+  max_recovery: 3
+exp: 60
+";
+
+#[test]
+fn versionless_save_is_migrated_to_current_format() {
+    enter_temp_dir();
+    let slot = "migrate";
+    fs::write(slot_path(slot), VERSIONLESS_SAVE).unwrap();
+
+    assert_eq!(migrate_save(slot).unwrap(), Some(0));
+    // 元のファイルはそのまま残る
+    assert_eq!(
+        fs::read_to_string(migration_backup_path(slot, 0)).unwrap(),
+        VERSIONLESS_SAVE
+    );
+
+    let data = fs::read_to_string(slot_path(slot)).unwrap();
+    let mut lines = data.lines();
+    assert!(lines.next().unwrap().starts_with("# checksum: "));
+    assert_eq!(
+        lines.next(),
+        Some(format!("version: {}", SAVE_VERSION).as_str())
+    );
+
+    // 無かった項目は、その形式を導入した時点のデフォルト値で補われる
+    let character = load_character(slot).unwrap();
+    assert_eq!(character.hero.name, "test");
+    assert_eq!(character.hero.exp, 60);
+    assert_eq!(character.hero.stats.speed, 5);
+    assert_eq!((character.hero.mp, character.hero.max_mp), (10, 10));
+    assert_eq!(character.gold, 0);
+    assert!(character.stages_cleared.is_empty());
+    assert!(character.companions.is_empty());
+    assert!(!character.game_over);
+
+    // 変換済みのセーブはもう変換しない
+    assert_eq!(migrate_save(slot).unwrap(), None);
+}
+
+#[test]
+fn save_round_trip_keeps_party() {
+    enter_temp_dir();
+    let slot = "round_trip";
+    let companion: CompanionDef = serde_yaml::from_str(
+        "
+name: 戦士ガルド
+join_stage: 1
+lv: 3
+hp: 60
+mp: 5
+stats:
+  min_attack: 4
+  max_attack: 7
+  min_recovery: 1
+  max_recovery: 2
+  speed: 6
+skills: [heavy_strike]
+",
+    )
+    .unwrap();
+
+    let mut character = Character::new(slot.to_string());
+    character.gold = 123;
+    character.hero.exp = 15;
+    character.stages_cleared.push(1);
+    character.companions.push(companion.to_member());
+    save_character(slot, &character).unwrap();
+
+    let loaded = load_character(slot).unwrap();
+    assert_eq!(loaded.hero.name, slot);
+    assert_eq!(loaded.hero.exp, 15);
+    assert_eq!(loaded.gold, 123);
+    assert_eq!(loaded.stages_cleared, vec![1]);
+    assert_eq!(loaded.companions.len(), 1);
+    let member = &loaded.companions[0];
+    assert_eq!(member.name, "戦士ガルド");
+    assert_eq!((member.lv, member.hp, member.max_hp), (3, 60, 60));
+    assert_eq!(member.stats.speed, 6);
+    assert_eq!(member.skills, vec!["heavy_strike".to_string()]);
+}
+
+#[test]
+fn checksum_mismatch_falls_back_to_backup() {
+    enter_temp_dir();
+    let slot = "checksum";
+
+    // 2回セーブすると、1回目の内容がバックアップに残る
+    let mut character = Character::new(slot.to_string());
+    character.gold = 10;
+    save_character(slot, &character).unwrap();
+    character.gold = 20;
+    save_character(slot, &character).unwrap();
+
+    // チェックサムを直さずに中身だけ書き換える
+    let data = fs::read_to_string(slot_path(slot)).unwrap();
+    assert!(data.contains("gold: 20"));
+    fs::write(slot_path(slot), data.replace("gold: 20", "gold: 99999")).unwrap();
+
+    let error = load_character(slot).unwrap_err();
+    assert!(error.to_string().contains("チェックサム"), "{}", error);
+
+    let (backup, restored) = restore_backup(slot).unwrap();
+    assert_eq!(backup, backup_path(slot, 1));
+    assert_eq!(restored.gold, 10);
+    // 壊れたセーブは退避され、スロットはバックアップの内容に戻る
+    assert!(fs::read_to_string(corrupt_path(slot))
+        .unwrap()
+        .contains("gold: 99999"));
+    assert_eq!(load_character(slot).unwrap().gold, 10);
+}
+
+#[test]
+fn slot_names_with_dots_are_rejected() {
+    enter_temp_dir();
+
+    // foo.v0 のバックアップは foo の変換前の写しと同じ foo.v0.bak になってしまう
+    assert!(validate_slot_name("delete_me.v0").is_err());
+    assert!(validate_slot_name("../outside").is_err());
+    assert!(validate_slot_name("").is_err());
+    assert!(validate_slot_name("unused_v0").is_ok());
+}
+
+#[test]
+fn delete_slot_removes_only_its_own_files() {
+    enter_temp_dir();
+    let slot = "delete_me";
+    let other = "delete_me_v0";
+
+    for name in [slot, other] {
+        let character = Character::new(name.to_string());
+        save_character(name, &character).unwrap();
+        save_character(name, &character).unwrap();
+        fs::write(migration_backup_path(name, 0), VERSIONLESS_SAVE).unwrap();
+        fs::write(corrupt_path(name), "").unwrap();
+    }
+
+    delete_slot(slot).unwrap();
+
+    for file in [
+        slot_path(slot),
+        backup_path(slot, 1),
+        migration_backup_path(slot, 0),
+        corrupt_path(slot),
+    ] {
+        assert!(!file.exists(), "{} が残っている", file.display());
+    }
+    for file in [
+        slot_path(other),
+        backup_path(other, 1),
+        migration_backup_path(other, 0),
+        corrupt_path(other),
+    ] {
+        assert!(file.exists(), "{} が消えた", file.display());
+    }
+}
+
+// ショップの在庫を保存する前（バージョン 3）の、仲間がいるセーブ
+const V3_SAVE: &str = "\
+version: 3
+name: v3
+lv: 4
+hp: 70
+max_hp: 70
+stats:
+  min_attack: 5
+  max_attack: 9
+  min_recovery: 2
+  max_recovery: 4
+  speed: 7
+exp: 12
+mp: 20
+max_mp: 20
+equipment: {}
+skills: [fire]
+status: []
+stages_cleared: [1]
+current_stage: null
+seed: null
+inventory:
+  potion: 2
+gold: 300
+companions:
+  - name: 戦士ガルド
+    lv: 3
+    hp: 60
+    max_hp: 60
+    stats:
+      min_attack: 4
+      max_attack: 7
+      min_recovery: 1
+      max_recovery: 2
+      speed: 6
+    exp: 0
+    mp: 5
+    max_mp: 5
+    equipment: {}
+    skills: [heavy_strike]
+    status: []
+defeat_rules:
+  gold_loss: 50
+game_over: false
+";
+
+#[test]
+fn v3_save_keeps_its_party_and_starts_with_full_shop_stock() {
+    enter_temp_dir();
+    let slot = "v3";
+    fs::write(slot_path(slot), V3_SAVE).unwrap();
+
+    assert_eq!(migrate_save(slot).unwrap(), Some(3));
+    assert_eq!(
+        fs::read_to_string(migration_backup_path(slot, 3)).unwrap(),
+        V3_SAVE
+    );
+
+    let character = load_character(slot).unwrap();
+    assert_eq!((character.hero.lv, character.gold), (4, 300));
+    assert_eq!(character.inventory.count("potion"), 2);
+    assert_eq!(character.defeat_rules.gold_loss, 50);
+    assert!(character.shop_stock.is_empty());
+    assert_eq!(character.companions.len(), 1);
+    assert_eq!(character.companions[0].name, "戦士ガルド");
+    assert_eq!(
+        character.companions[0].skills,
+        vec!["heavy_strike".to_string()]
+    );
+}
+
+#[test]
+fn only_the_newest_backups_are_kept() {
+    enter_temp_dir();
+    let slot = "rotate";
+
+    let mut character = Character::new(slot.to_string());
+    for gold in 1..=5 {
+        character.gold = gold;
+        save_character(slot, &character).unwrap();
+    }
+
+    // 新しいものから順に 4, 3, 2 ゴールドの時点のセーブが残る
+    for n in 1..=SAVE_BACKUPS {
+        let backup = fs::read_to_string(backup_path(slot, n)).unwrap();
+        assert!(backup.contains(&format!("gold: {}\n", 5 - n)), "{}", backup);
+    }
+    assert!(!backup_path(slot, SAVE_BACKUPS + 1).exists());
+    // 書き込み途中のファイルは残らない
+    assert!(!slot_path(slot).with_extension("yaml.tmp").exists());
+    assert_eq!(load_character(slot).unwrap().gold, 5);
+}
+
+#[test]
+fn fallback_skips_a_corrupt_newest_backup() {
+    enter_temp_dir();
+    let slot = "fallback";
+
+    let mut character = Character::new(slot.to_string());
+    for gold in [10, 20, 30] {
+        character.gold = gold;
+        save_character(slot, &character).unwrap();
+    }
+    // セーブと一番新しいバックアップ（20 ゴールド）の両方を壊す
+    fs::write(slot_path(slot), "gold: [").unwrap();
+    let newest = fs::read_to_string(backup_path(slot, 1)).unwrap();
+    fs::write(backup_path(slot, 1), newest.replace("gold: 20", "gold: 25")).unwrap();
+
+    let (backup, restored) = restore_backup(slot).unwrap();
+    assert_eq!(backup, backup_path(slot, 2));
+    assert_eq!(restored.gold, 10);
+    assert_eq!(load_character(slot).unwrap().gold, 10);
+}
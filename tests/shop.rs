@@ -0,0 +1,83 @@
+// ショップの売買と在庫の補充を確かめる
+
+use kt_rpg::{
+    battle::BattleEvent,
+    character::Character,
+    shop::{buy, restock, sell, ShopEntry, ShopError},
+};
+
+fn entries() -> Vec<ShopEntry> {
+    serde_yaml::from_str(
+        "
+- item: potion
+  price: 20
+- item: hi_potion
+  price: 80
+  stock: 2
+",
+    )
+    .unwrap()
+}
+
+fn rich_character() -> Character {
+    let mut character = Character::new("勇者".to_string());
+    character.gold = 1000;
+    character
+}
+
+#[test]
+fn limited_stock_is_kept_until_a_stage_is_cleared() {
+    let mut character = rich_character();
+    let shop = entries();
+
+    buy(&mut character, &shop[1]).unwrap();
+    buy(&mut character, &shop[1]).unwrap();
+    assert_eq!(buy(&mut character, &shop[1]), Err(ShopError::OutOfStock));
+
+    // 店を出て入り直しても（shop.yaml を読み直しても）在庫は戻らない
+    let shop = entries();
+    assert_eq!(shop[1].stock_left(&character), Some(0));
+    assert_eq!(buy(&mut character, &shop[1]), Err(ShopError::OutOfStock));
+    assert_eq!(shop[0].stock_left(&character), None);
+
+    assert_eq!(restock(&mut character), vec![BattleEvent::ShopRestocked]);
+    assert_eq!(shop[1].stock_left(&character), Some(2));
+    // 減っていなければ補充の知らせは出ない
+    assert_eq!(restock(&mut character), Vec::new());
+}
+
+#[test]
+fn loot_not_sold_in_the_shop_can_be_sold_for_half_its_price() {
+    let mut character = rich_character();
+    character.inventory.add("dragon_scale", 1);
+
+    assert_eq!(
+        sell(&mut character, &entries(), "dragon_scale", 300),
+        Ok(150)
+    );
+    assert_eq!(character.gold, 1150);
+    assert_eq!(character.inventory.count("dragon_scale"), 0);
+    assert_eq!(
+        sell(&mut character, &entries(), "dragon_scale", 300),
+        Err(ShopError::NotOwned)
+    );
+
+    // 値段の無いものは売れず、所持品も減らない
+    character.inventory.add("old_key", 1);
+    assert_eq!(
+        sell(&mut character, &entries(), "old_key", 0),
+        Err(ShopError::NotForSale)
+    );
+    assert_eq!(character.inventory.count("old_key"), 1);
+}
+
+#[test]
+fn selling_a_limited_item_puts_it_back_in_stock() {
+    let mut character = rich_character();
+    let shop = entries();
+
+    buy(&mut character, &shop[1]).unwrap();
+    assert_eq!(shop[1].stock_left(&character), Some(1));
+    assert_eq!(sell(&mut character, &shop, "hi_potion", 80), Ok(40));
+    assert_eq!(shop[1].stock_left(&character), Some(2));
+}
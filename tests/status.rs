@@ -0,0 +1,128 @@
+// 状態異常のダメージ、行動不能、付与、回復を確かめる
+
+mod common;
+
+use common::{empty_content, hero, monsters};
+use kt_rpg::{
+    battle::{Action, Battle, BattleEvent},
+    status::{cure, StatusEffect, StatusKind},
+    GameRng,
+};
+use rand::SeedableRng;
+
+// 倒されないよう HP を多くした、攻撃の弱いモンスター
+fn dummy(inflicts: &str) -> Battle {
+    Battle::new(monsters(&format!(
+        "
+- name: かかし
+  hp: 999
+  max_hp: 999
+  min_attack: 1
+  max_attack: 1
+  exp: 1
+  speed: 1
+  inflicts: {}
+",
+        inflicts
+    )))
+}
+
+fn effect(kind: StatusKind, turns: u32) -> StatusEffect {
+    StatusEffect { kind, turns }
+}
+
+#[test]
+fn poison_deals_a_tenth_of_max_hp_before_acting_and_wears_off() {
+    let mut character = hero();
+    character.hero.status = vec![effect(StatusKind::Poison, 1)];
+    let mut battle = dummy("[]");
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let events = battle.turn(
+        &mut character,
+        vec![Action::Defend],
+        &empty_content(),
+        &mut rng,
+    );
+
+    assert_eq!(
+        events[..3],
+        [
+            BattleEvent::StatusDamage {
+                target: "勇者".to_string(),
+                status: StatusKind::Poison,
+                damage: 5,
+            },
+            BattleEvent::StatusRecovered {
+                target: "勇者".to_string(),
+                status: StatusKind::Poison,
+            },
+            BattleEvent::Defend {
+                actor: "勇者".to_string(),
+            },
+        ]
+    );
+    assert!(character.hero.status.is_empty());
+}
+
+#[test]
+fn sleeping_member_cannot_act() {
+    let mut character = hero();
+    character.hero.status = vec![effect(StatusKind::Sleep, 2)];
+    let mut battle = dummy("[]");
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let events = battle.turn(
+        &mut character,
+        vec![Action::Attack(0)],
+        &empty_content(),
+        &mut rng,
+    );
+
+    assert_eq!(
+        events[0],
+        BattleEvent::StatusBlocked {
+            target: "勇者".to_string(),
+            status: StatusKind::Sleep,
+        }
+    );
+    assert!(!events
+        .iter()
+        .any(|e| matches!(e, BattleEvent::Attack { .. })));
+    assert_eq!(battle.enemies()[0].hp(), 999);
+    assert_eq!(character.hero.status, vec![effect(StatusKind::Sleep, 1)]);
+}
+
+#[test]
+fn monster_hit_inflicts_its_status() {
+    let mut character = hero();
+    let mut battle = dummy("[{ status: burn, turns: 3, chance: 1.0 }]");
+    let mut rng = GameRng::seed_from_u64(1);
+
+    let events = battle.turn(
+        &mut character,
+        vec![Action::Attack(0)],
+        &empty_content(),
+        &mut rng,
+    );
+
+    assert!(events.contains(&BattleEvent::StatusInflicted {
+        target: "勇者".to_string(),
+        status: StatusKind::Burn,
+        turns: 3,
+    }));
+    assert_eq!(character.hero.status, vec![effect(StatusKind::Burn, 3)]);
+}
+
+#[test]
+fn cure_removes_only_the_given_status() {
+    let mut effects = vec![effect(StatusKind::Poison, 2), effect(StatusKind::Burn, 2)];
+
+    assert_eq!(
+        cure(&mut effects, Some(StatusKind::Burn)),
+        vec![StatusKind::Burn]
+    );
+    assert_eq!(effects, vec![effect(StatusKind::Poison, 2)]);
+    assert_eq!(cure(&mut effects, None), vec![StatusKind::Poison]);
+    assert!(effects.is_empty());
+}
@@ -0,0 +1,180 @@
+// データファイルの検証を、リポジトリのデータを書き換えたもので確かめる
+// データは作業ディレクトリから読まれるので、一時ディレクトリに写してから動かす
+
+use std::{
+    env, fs,
+    path::Path,
+    process,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use kt_rpg::{content::Content, error::GameError, validate::validate_content};
+
+const DATA_FILES: [&str; 7] = [
+    "monsters.yaml",
+    "items.yaml",
+    "equipment.yaml",
+    "shop.yaml",
+    "skills.yaml",
+    "companions.yaml",
+    "stages.yaml",
+];
+
+// 作業ディレクトリはプロセス全体で共有されるので、移るのは一度だけにする
+// テストは並んで動くため、データを書き換えている間は他のテストを待たせる
+fn enter_data_dir() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    static DIR: OnceLock<()> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = env::temp_dir().join(format!("kt_rpg_validate_test_{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for file in DATA_FILES {
+            fs::copy(
+                Path::new(env!("CARGO_MANIFEST_DIR")).join(file),
+                dir.join(file),
+            )
+            .unwrap();
+        }
+        env::set_current_dir(&dir).unwrap();
+    });
+    LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// file を data に書き換えて検証し、読み込みのエラーと validate の問題を返す（終わったら元に戻す）
+fn check_with(file: &str, data: &str) -> (Option<GameError>, Vec<GameError>) {
+    let _guard = enter_data_dir();
+    let original = fs::read_to_string(file).unwrap();
+    fs::write(file, data).unwrap();
+
+    let loaded = Content::load().err();
+    let problems = validate_content();
+
+    fs::write(file, original).unwrap();
+    (loaded, problems)
+}
+
+// 元のデータファイル
+fn original(file: &str) -> String {
+    fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join(file)).unwrap()
+}
+
+// changed を data に書き換えると、読み込みと validate が同じ1件の問題を
+// reported の line 行目として報告することを確かめる
+fn assert_reported(changed: &str, data: &str, reported: &str, line: usize, message: &str) {
+    let (loaded, problems) = check_with(changed, data);
+
+    let loaded = loaded.expect("読み込みが失敗するはず").to_string();
+    assert!(
+        loaded.starts_with(&format!("{}:{}: ", reported, line)),
+        "{}",
+        loaded
+    );
+    assert!(loaded.contains(message), "{}", loaded);
+    assert_eq!(problems.len(), 1, "{:?}", problems);
+    assert_eq!(problems[0].to_string(), loaded);
+}
+
+#[test]
+fn repository_data_has_no_problems() {
+    let _guard = enter_data_dir();
+
+    assert!(Content::load().is_ok());
+    assert!(validate_content().is_empty());
+}
+
+#[test]
+fn stage_boss_must_be_marked_as_boss() {
+    // 最初のボス（最初のステージのボス）を is_boss: false にする
+    let data = original("monsters.yaml").replacen("is_boss: true", "is_boss: false", 1);
+    let first_stage = original("stages.yaml")
+        .lines()
+        .position(|line| line.starts_with('-'))
+        .unwrap()
+        + 1;
+
+    // 問題はボスを指定した stages.yaml の側で報告される
+    assert_reported(
+        "monsters.yaml",
+        &data,
+        "stages.yaml",
+        first_stage,
+        "is_boss: true になっていません",
+    );
+}
+
+#[test]
+fn parse_error_reports_file_and_line() {
+    let items = original("items.yaml");
+    let line = items
+        .lines()
+        .position(|line| line.trim() == "price: 20")
+        .unwrap()
+        + 1;
+    let data = items.replacen("price: 20", "price: lots", 1);
+
+    let (loaded, problems) = check_with("items.yaml", &data);
+
+    let prefix = format!("items.yaml:{}:", line);
+    let loaded = loaded.expect("読み込みが失敗するはず").to_string();
+    assert!(loaded.starts_with(&prefix), "{}", loaded);
+    assert!(problems
+        .iter()
+        .any(|problem| problem.to_string().starts_with(&prefix)));
+}
+
+#[test]
+fn indented_list_reports_the_line_of_the_entry() {
+    // 一覧全体を字下げし、最初のアイテムと同じIDのアイテムを最後に足す
+    let mut data: String = original("items.yaml")
+        .lines()
+        .map(|line| format!("  {}\n", line))
+        .collect();
+    data.push('\n');
+    let line = data.lines().count() + 1;
+    data.push_str(
+        "  - id: potion\n    name: \"ポーション\"\n    effect: { type: heal, amount: 30 }\n",
+    );
+
+    assert_reported(
+        "items.yaml",
+        &data,
+        "items.yaml",
+        line,
+        "アイテムID potion が重複しています",
+    );
+}
+
+#[test]
+fn flow_style_list_reports_the_line_of_the_entry() {
+    // 商品を1行ずつ { item: ..., price: ... } で書き、最初の商品をもう一度最後に並べる
+    let mut entries: Vec<String> = Vec::new();
+    for line in original("shop.yaml").lines() {
+        let line = line.trim();
+        if let Some(field) = line.strip_prefix("- ") {
+            entries.push(field.to_string());
+        } else if !line.is_empty() && !line.starts_with('#') {
+            let entry = entries.last_mut().unwrap();
+            entry.push_str(", ");
+            entry.push_str(line);
+        }
+    }
+    entries.push(entries[0].clone());
+    let data = format!(
+        "# 商品の一覧\n[\n{}\n]\n",
+        entries
+            .iter()
+            .map(|entry| format!("  {{ {} }}", entry))
+            .collect::<Vec<_>>()
+            .join(",\n")
+    );
+
+    // コメントと "[" の2行の後に並ぶ
+    assert_reported(
+        "shop.yaml",
+        &data,
+        "shop.yaml",
+        entries.len() + 2,
+        "が重複しています",
+    );
+}